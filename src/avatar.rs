@@ -0,0 +1,414 @@
+use crate::config_manager::{get_cache_dir, AvatarConfig, AvatarProtocol};
+use crate::github_client::client_builder;
+use crate::layout::{beside, GAP};
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use colored::{Colorize, CustomColor};
+use http_body_util::BodyExt;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::io::{self, Cursor};
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+
+/// Pixels per terminal cell assumed when sizing images for the graphics
+/// protocols. Only sixel depends on it; the others scale to the cells given.
+const CELL_WIDTH: u32 = 10;
+const CELL_HEIGHT: u32 = 20;
+
+/// Base64 bytes per kitty escape sequence, the most the protocol allows.
+const KITTY_CHUNK: usize = 4096;
+
+pub enum Avatar {
+    /// Half-block art, one string per terminal row.
+    Blocks(Vec<String>),
+    /// An inline image escape sequence covering `columns` by `rows` cells.
+    Graphic {
+        sequence: String,
+        columns: u16,
+        rows: u16,
+    },
+}
+
+impl Avatar {
+    /// The avatar with `lines` to its right.
+    pub fn beside(&self, lines: &[String]) -> String {
+        match self {
+            Avatar::Blocks(art) => beside(art, lines),
+            Avatar::Graphic {
+                sequence,
+                columns,
+                rows,
+            } => {
+                let rows = *rows as usize;
+                // Make room first so scrolling can't separate the image from
+                // the text, and keep the cursor where it was as the protocols
+                // disagree on where they leave it
+                let mut out = "\n".repeat(rows);
+                out += &format!("\x1b[{}A\x1b7{}\x1b8", rows, sequence);
+                for row in 0..rows.max(lines.len()) {
+                    if let Some(line) = lines.get(row) {
+                        out += &format!("\x1b[{}C{}", *columns as usize + GAP, line);
+                    }
+                    out += "\n";
+                }
+                out
+            }
+        }
+    }
+}
+
+/// Loads `username`'s avatar from the cache or from `host`, drawn as
+/// configured.
+pub async fn load(username: &str, host: &str, avatar_config: &AvatarConfig) -> Result<Avatar> {
+    let image = avatar_image(username, host, avatar_config).await?;
+    render(&image, avatar_config.protocol, avatar_config.width)
+}
+
+/// Draws `image` `columns` cells wide, keeping its aspect ratio.
+pub fn render(image: &DynamicImage, protocol: AvatarProtocol, columns: u16) -> Result<Avatar> {
+    let columns = columns.max(1);
+    // Cells are about twice as tall as they are wide
+    let rows = (u32::from(columns) * image.height() / (image.width().max(1) * 2)).max(1) as u16;
+    let protocol = match protocol {
+        AvatarProtocol::Auto => detect_protocol(),
+        protocol => protocol,
+    };
+
+    let graphic = |sequence| Avatar::Graphic {
+        sequence,
+        columns,
+        rows,
+    };
+    let pixels = || {
+        image.resize_exact(
+            u32::from(columns) * CELL_WIDTH,
+            u32::from(rows) * CELL_HEIGHT,
+            FilterType::Triangle,
+        )
+    };
+    Ok(match protocol {
+        AvatarProtocol::Kitty => graphic(kitty(&png(&pixels())?, columns, rows)),
+        AvatarProtocol::Iterm => graphic(iterm(&png(&pixels())?, columns, rows)),
+        AvatarProtocol::Sixel => graphic(sixel(&pixels().to_rgba8())),
+        AvatarProtocol::Auto | AvatarProtocol::Blocks => {
+            Avatar::Blocks(blocks(image, columns, rows))
+        }
+    })
+}
+
+/// Kitty and iTerm2 announce themselves in the environment. Terminals that
+/// support sixel don't, so it has to be chosen in the config.
+fn detect_protocol() -> AvatarProtocol {
+    let term = env::var("TERM").unwrap_or_default();
+    let program = env::var("TERM_PROGRAM").unwrap_or_default();
+    if env::var_os("TMUX").is_some() {
+        // tmux swallows graphics escapes unless configured to pass them on
+        AvatarProtocol::Blocks
+    } else if env::var_os("KITTY_WINDOW_ID").is_some()
+        || term == "xterm-kitty"
+        || program == "ghostty"
+    {
+        AvatarProtocol::Kitty
+    } else if program == "iTerm.app" || program == "WezTerm" {
+        AvatarProtocol::Iterm
+    } else {
+        AvatarProtocol::Blocks
+    }
+}
+
+/// The cached avatar while it's newer than `cache_days`, otherwise a fresh
+/// download. An outdated avatar is still used when downloading fails. Cached
+/// files that don't decode, say after a crash, are deleted and downloaded again.
+async fn avatar_image(
+    username: &str,
+    host: &str,
+    avatar_config: &AvatarConfig,
+) -> Result<DynamicImage> {
+    // Usernames come from the command line; keep them from escaping the cache
+    let file_name: String = username
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect::<String>()
+        .to_lowercase();
+    let path = get_cache_dir()
+        .filter(|_| !file_name.is_empty())
+        .map(|dir| dir.join("avatars").join(host).join(file_name));
+    let max_age = Duration::from_secs(avatar_config.cache_days * 24 * 60 * 60);
+
+    if let Some(path) = &path {
+        let fresh = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age < max_age);
+        if fresh {
+            if let Some(image) = read_cached(path) {
+                return Ok(image);
+            }
+        }
+    }
+
+    let downloaded = async {
+        let bytes = download(username, host).await?;
+        let image = image::load_from_memory(&bytes).context("Could not decode the avatar")?;
+        Ok::<_, anyhow::Error>((bytes, image))
+    }
+    .await;
+    match downloaded {
+        Ok((bytes, image)) => {
+            // Caching is best effort; the avatar shows either way
+            if let Some(path) = &path {
+                let _ = write_cache(path, &bytes);
+            }
+            Ok(image)
+        }
+        Err(e) => path.as_deref().and_then(read_cached).ok_or(e),
+    }
+}
+
+/// Decodes a cached avatar, deleting the file when it's unreadable.
+fn read_cached(path: &Path) -> Option<DynamicImage> {
+    let image = fs::read(path)
+        .ok()
+        .and_then(|bytes| image::load_from_memory(&bytes).ok());
+    if image.is_none() {
+        let _ = fs::remove_file(path);
+    }
+    image
+}
+
+/// Writes to a temporary file beside `path` and renames it into place, so an
+/// interrupted write never leaves a truncated avatar in the cache.
+fn write_cache(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(dir)?;
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(format!(".{}.tmp", process::id()));
+    let temp = dir.join(temp_name);
+    fs::write(&temp, bytes)?;
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+/// Downloads the avatar from the profile's `.png` address, which redirects to
+/// the image. No token is sent, as the image host is a different one.
+async fn download(username: &str, host: &str) -> Result<Vec<u8>> {
+    let octocrab = client_builder()
+        .base_uri(format!("https://{}", host))?
+        .build()?;
+    let response = octocrab
+        ._get(format!("/{}.png?size=256", username))
+        .await
+        .with_context(|| format!("Could not download the avatar of {}", username))?;
+    if !response.status().is_success() {
+        bail!(
+            "Could not download the avatar of {}: {} returned {}",
+            username,
+            host,
+            response.status()
+        );
+    }
+    Ok(response.into_body().collect().await?.to_bytes().to_vec())
+}
+
+fn png(image: &DynamicImage) -> Result<Vec<u8>> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Two pixels per cell: the upper one as the foreground of `▀`, the lower one
+/// as its background. Transparent pixels are left to the terminal background.
+fn blocks(image: &DynamicImage, columns: u16, rows: u16) -> Vec<String> {
+    let pixels = image
+        .resize_exact(
+            u32::from(columns),
+            u32::from(rows) * 2,
+            FilterType::Triangle,
+        )
+        .to_rgba8();
+    (0..u32::from(rows))
+        .map(|row| {
+            (0..u32::from(columns))
+                .map(|column| {
+                    let top = opaque(pixels.get_pixel(column, row * 2));
+                    let bottom = opaque(pixels.get_pixel(column, row * 2 + 1));
+                    match (top, bottom) {
+                        (Some(top), Some(bottom)) => {
+                            "▀".custom_color(top).on_custom_color(bottom).to_string()
+                        }
+                        (Some(top), None) => "▀".custom_color(top).to_string(),
+                        (None, Some(bottom)) => "▄".custom_color(bottom).to_string(),
+                        (None, None) => " ".to_string(),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn opaque(pixel: &Rgba<u8>) -> Option<CustomColor> {
+    let [r, g, b, a] = pixel.0;
+    (a >= 128).then_some(CustomColor { r, g, b })
+}
+
+/// Kitty's graphics protocol, with the PNG split over as many escape
+/// sequences as it takes.
+fn kitty(png: &[u8], columns: u16, rows: u16) -> String {
+    let data = STANDARD.encode(png);
+    let mut out = String::new();
+    for start in (0..data.len()).step_by(KITTY_CHUNK) {
+        let end = (start + KITTY_CHUNK).min(data.len());
+        let more = u8::from(end < data.len());
+        if start == 0 {
+            out += &format!(
+                "\x1b_Ga=T,f=100,q=2,c={},r={},m={};{}\x1b\\",
+                columns,
+                rows,
+                more,
+                &data[start..end]
+            );
+        } else {
+            out += &format!("\x1b_Gm={};{}\x1b\\", more, &data[start..end]);
+        }
+    }
+    out
+}
+
+/// iTerm2's inline image protocol, also understood by WezTerm.
+fn iterm(png: &[u8], columns: u16, rows: u16) -> String {
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=0:{}\x07",
+        png.len(),
+        columns,
+        rows,
+        STANDARD.encode(png)
+    )
+}
+
+/// Sixel graphics over a 6×6×6 color cube. Transparent pixels stay
+/// unpainted.
+fn sixel(pixels: &RgbaImage) -> String {
+    let (width, height) = pixels.dimensions();
+    let color = |x: u32, y: u32| {
+        let [r, g, b, a] = pixels.get_pixel(x, y).0;
+        let level = |value: u8| usize::from(value) * 6 / 256;
+        (a >= 128).then(|| level(r) * 36 + level(g) * 6 + level(b))
+    };
+
+    let mut out = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+    for index in 0..216 {
+        // Sixel color components are percentages
+        let percent = |level: usize| level * 100 / 5;
+        out += &format!(
+            "#{};2;{};{};{}",
+            index,
+            percent(index / 36),
+            percent(index / 6 % 6),
+            percent(index % 6)
+        );
+    }
+    for band in (0..height).step_by(6) {
+        let band_height = (height - band).min(6);
+        let used: BTreeSet<usize> = (0..width)
+            .flat_map(|x| (0..band_height).filter_map(move |dy| color(x, band + dy)))
+            .collect();
+        for index in used {
+            let sixels: Vec<char> = (0..width)
+                .map(|x| {
+                    let bits = (0..band_height)
+                        .filter(|&dy| color(x, band + dy) == Some(index))
+                        .fold(0, |bits, dy| bits | 1 << dy);
+                    char::from(63 + bits as u8)
+                })
+                .collect();
+            out += &format!("#{}{}$", index, run_length(&sixels));
+        }
+        out += "-";
+    }
+    out + "\x1b\\"
+}
+
+/// Sixel's `!count` repeat syntax for runs longer than it saves.
+fn run_length(sixels: &[char]) -> String {
+    let mut out = String::new();
+    let mut rest = sixels;
+    while let Some(&first) = rest.first() {
+        let run = rest.iter().take_while(|&&c| c == first).count();
+        if run > 3 {
+            out += &format!("!{}{}", run, first);
+        } else {
+            out.extend(std::iter::repeat_n(first, run));
+        }
+        rest = &rest[run..];
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::visible_width;
+
+    /// 4×4 pixels: the upper half opaque red, the lower half transparent.
+    const FIXTURE: &[u8] = include_bytes!("../tests/fixtures/avatar.png");
+
+    fn fixture() -> DynamicImage {
+        image::load_from_memory(FIXTURE).unwrap()
+    }
+
+    #[test]
+    fn blocks_leave_transparent_cells_blank() {
+        let Avatar::Blocks(lines) = render(&fixture(), AvatarProtocol::Blocks, 4).unwrap() else {
+            panic!("expected half blocks");
+        };
+        assert_eq!(lines.len(), 2);
+        assert_eq!(visible_width(&lines[0]), 4);
+        assert_eq!(lines[0].matches('▀').count(), 4);
+        assert_eq!(lines[1], "    ");
+    }
+
+    #[test]
+    fn truncated_cache_files_are_deleted() {
+        let dir = env::temp_dir().join(format!("gitfetch-avatar-test-{}", process::id()));
+        let path = dir.join("octocat");
+        write_cache(&path, FIXTURE).unwrap();
+        assert!(read_cached(&path).is_some());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::write(&path, &FIXTURE[..FIXTURE.len() / 2]).unwrap();
+        assert!(read_cached(&path).is_none());
+        assert!(!path.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn kitty_splits_large_images_into_chunks() {
+        let sequence = kitty(&[0; 10_000], 20, 10);
+        // 10000 bytes are 13336 base64 characters, so four chunks
+        assert_eq!(sequence.matches("\x1b_G").count(), 4);
+        assert!(sequence.starts_with("\x1b_Ga=T,f=100,q=2,c=20,r=10,m=1;"));
+        assert_eq!(sequence.matches("m=1;").count(), 3);
+        assert_eq!(sequence.matches("m=0;").count(), 1);
+    }
+
+    #[test]
+    fn sixel_paints_only_opaque_pixels() {
+        let sequence = sixel(&fixture().to_rgba8());
+        // The red maps to level 4,1,1 of the cube; the upper two rows are set
+        assert!(sequence.ends_with("#151!4B$-\x1b\\"));
+        assert_eq!(sequence.matches('$').count(), 1);
+    }
+
+    #[test]
+    fn run_length_only_compresses_long_runs() {
+        assert_eq!(run_length(&['?', '?', '?', '~', '~', '~', '~']), "???!4~");
+    }
+}
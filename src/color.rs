@@ -0,0 +1,82 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// Decides whether output is colored, for `--color auto|always|never`.
+/// `auto` follows the NO_COLOR and CLICOLOR_FORCE conventions, then colors
+/// only when stdout is a terminal.
+pub fn set_choice(choice: &str) {
+    let enabled = match choice {
+        "always" => true,
+        "never" => false,
+        _ => auto(),
+    };
+    colored::control::set_override(enabled);
+}
+
+pub fn enabled() -> bool {
+    colored::control::SHOULD_COLORIZE.should_colorize()
+}
+
+fn auto() -> bool {
+    auto_from(|key| env::var(key).ok(), io::stdout().is_terminal())
+}
+
+/// `auto` with the environment and whether stdout is a terminal passed in.
+fn auto_from(var: impl Fn(&str) -> Option<String>, terminal: bool) -> bool {
+    let set = |key: &str| var(key).is_some_and(|value| !value.is_empty());
+    if set("NO_COLOR") {
+        false
+    } else if set("CLICOLOR_FORCE") && var("CLICOLOR_FORCE").as_deref() != Some("0") {
+        true
+    } else if var("CLICOLOR").as_deref() == Some("0") {
+        false
+    } else {
+        terminal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auto_with(vars: &[(&str, &str)], terminal: bool) -> bool {
+        auto_from(
+            |key| {
+                vars.iter()
+                    .find(|&&(name, _)| name == key)
+                    .map(|&(_, value)| value.to_string())
+            },
+            terminal,
+        )
+    }
+
+    #[test]
+    fn terminals_get_colors_unless_the_environment_says_otherwise() {
+        assert!(auto_with(&[], true));
+        assert!(!auto_with(&[], false));
+        assert!(!auto_with(&[("CLICOLOR", "0")], true));
+        assert!(auto_with(&[("CLICOLOR", "1")], true));
+    }
+
+    #[test]
+    fn no_color_wins_over_clicolor_force() {
+        assert!(!auto_with(&[("NO_COLOR", "1")], true));
+        assert!(!auto_with(
+            &[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")],
+            true
+        ));
+        // An empty NO_COLOR doesn't count
+        assert!(auto_with(&[("NO_COLOR", "")], true));
+    }
+
+    #[test]
+    fn clicolor_force_colors_pipes_and_wins_over_clicolor() {
+        assert!(auto_with(&[("CLICOLOR_FORCE", "1")], false));
+        assert!(auto_with(
+            &[("CLICOLOR_FORCE", "1"), ("CLICOLOR", "0")],
+            false
+        ));
+        assert!(!auto_with(&[("CLICOLOR_FORCE", "0")], false));
+        assert!(!auto_with(&[("CLICOLOR_FORCE", "")], false));
+    }
+}
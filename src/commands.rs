@@ -0,0 +1,619 @@
+use crate::avatar;
+use crate::compare;
+use crate::config_manager::{Config, LineConfig, DEFAULT_HOST};
+use crate::contribution_analyzer::ContributionData;
+use crate::contribution_source::{ContributionSource, Provider};
+use crate::day::{render_day, DayActivity};
+use crate::errors::FetchError;
+use crate::github_client::{
+    build_client, client_builder, day_range, fetch_commit_times, fetch_day_activity,
+    fetch_team_members, fetch_token_status, has_read_user_scope, parse_year, year_bounds,
+};
+use crate::graph_generator::{
+    colorize_info, colorize_logo, generate_contribution_graph, generate_punchcard,
+    render_colored_graph,
+};
+use crate::info::{contribution_info, load_profile, InfoLine};
+use crate::layout;
+use crate::local_git;
+use crate::logo;
+use crate::profile::Profile;
+use crate::punchcard;
+use crate::status_line;
+use crate::team::{self, TeamEntry};
+use crate::timezone;
+use crate::token_resolver::{self, StoredIn, TokenSource};
+use crate::tui;
+use crate::watch;
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDate};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// What a command prints, and the warnings about what it had to leave out.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct Output {
+    pub text: String,
+    pub warnings: Vec<String>,
+}
+
+impl Output {
+    fn new(text: String, warnings: Vec<String>) -> Self {
+        Output { text, warnings }
+    }
+}
+
+/// What the commands showing contributions share.
+#[derive(Clone, Copy)]
+pub struct Options<'a> {
+    pub config: &'a Config,
+    /// A calendar year, or the current one when `year_specified` is false.
+    pub year: &'a str,
+    /// Whether to show `year` rather than the past year up to today.
+    pub year_specified: bool,
+    /// Whether to color the output, usually `color::enabled()`.
+    pub colors: bool,
+}
+
+/// Picks the API when a token is available for `host`, falling back to the
+/// public profile page on github.com with a warning.
+pub fn connect(config: &Config, host: &str) -> Result<(ContributionSource, Option<String>)> {
+    let cross_year_streaks = config
+        .stats
+        .as_ref()
+        .is_some_and(|stats| stats.cross_year_streaks);
+    let mut from_gh = false;
+    let (provider, warning) = match token_resolver::resolve_token(config, host)? {
+        Some(token) => {
+            from_gh = token.source == TokenSource::GhHostsFile;
+            let octocrab = build_client(config.api_url(host).as_deref(), token.value)?;
+            (Provider::GitHub(octocrab), None)
+        }
+        None if host != DEFAULT_HOST => {
+            return Err(FetchError::MissingToken(format!(
+                "no token found for {}; run 'gitfetch token add --host {} <TOKEN>'",
+                host, host
+            ))
+            .into())
+        }
+        None => (
+            Provider::PublicProfile,
+            Some("No GitHub token found, showing public profile data only. Run 'gitfetch add-token <TOKEN>', set GITHUB_TOKEN, or configure token_command for full data.".to_string()),
+        ),
+    };
+    Ok((
+        ContributionSource::new(provider, cross_year_streaks).token_from_gh(from_gh),
+        warning,
+    ))
+}
+
+/// The graph with the info panel below it, beside the avatar or logo when
+/// one is configured. The avatar needs colors; without them the logo takes
+/// its place, drawn plain.
+pub async fn summary(
+    source: &ContributionSource,
+    username: &str,
+    host: &str,
+    options: &Options<'_>,
+) -> Result<Output> {
+    let Options {
+        config,
+        year,
+        year_specified,
+        colors,
+    } = *options;
+    let mut warnings = Vec::new();
+    let contributions = source.fetch(username, year, year_specified).await?;
+    let profile = profile(source, username, config, &mut warnings).await;
+    let info = contribution_info(username, year, &contributions, profile.as_ref(), config);
+    let graph =
+        generate_contribution_graph(&contributions.daily_contributions, year_specified, config);
+
+    // Without colors an avatar is either blank blocks or escape sequences
+    let avatar = match config
+        .avatar
+        .as_ref()
+        .filter(|avatar| avatar.enabled && colors)
+    {
+        Some(avatar_config) => avatar::load(username, host, avatar_config)
+            .await
+            .map_err(|e| warnings.push(format!("could not show the avatar: {:#}", e)))
+            .ok(),
+        None => None,
+    };
+    let logo = match config.logo.as_ref().filter(|_| avatar.is_none()) {
+        Some(logo_config) => logo::load(logo_config).unwrap_or_else(|e| {
+            warnings.push(format!("{:#}", e));
+            None
+        }),
+        None => None,
+    };
+
+    let info = colorize_info(&info, config, colors);
+    let panel = match (avatar, logo) {
+        (Some(avatar), _) => avatar.beside(&info),
+        (None, Some(logo)) => layout::beside(&colorize_logo(&logo, config, colors), &info),
+        (None, None) => info.iter().map(|line| format!("{}\n", line)).collect(),
+    };
+    let text = render_colored_graph(&graph, &[], config, colors) + &panel;
+    Ok(Output::new(text, warnings))
+}
+
+/// A one-line summary for status bars, or waybar JSON with the info panel as
+/// its tooltip.
+pub async fn status_line(
+    source: &ContributionSource,
+    username: &str,
+    waybar: bool,
+    options: &Options<'_>,
+) -> Result<Output> {
+    let Options {
+        config,
+        year,
+        year_specified,
+        ..
+    } = *options;
+    let mut warnings = Vec::new();
+    let contributions = source.fetch(username, year, year_specified).await?;
+    let default_line_config = LineConfig::default();
+    let line_config = config.line.as_ref().unwrap_or(&default_line_config);
+    let line = status_line::render_line(username, year, &contributions, line_config);
+    let text = if waybar {
+        let profile = profile(source, username, config, &mut warnings).await;
+        let tooltip: Vec<String> =
+            contribution_info(username, year, &contributions, profile.as_ref(), config)
+                .iter()
+                .map(|line| line.to_plain(config))
+                .collect();
+        status_line::render_waybar(&line, &tooltip, &contributions)
+    } else {
+        line
+    };
+    Ok(Output::new(text + "\n", warnings))
+}
+
+/// Two users' graphs and stats for the same range, or with `diverging` one
+/// graph colored by who contributed more each day. Side by side graphs need
+/// the terminal `width` to tell whether they fit.
+pub async fn compare_users(
+    source: &ContributionSource,
+    (first_name, second_name): (&str, &str),
+    diverging: bool,
+    width: Option<usize>,
+    options: &Options<'_>,
+) -> Result<Output> {
+    let Options {
+        config,
+        year,
+        year_specified,
+        colors,
+    } = *options;
+    let (first, second) = tokio::join!(
+        source.fetch(first_name, year, year_specified),
+        source.fetch(second_name, year, year_specified)
+    );
+    let (first, second) = ((first_name, &first?), (second_name, &second?));
+    let text = if diverging {
+        compare::render_diverging(first, second, year, year_specified, config, colors)
+    } else {
+        compare::render_side_by_side(first, second, year, year_specified, width, config, colors)
+    };
+    Ok(Output::new(text, Vec::new()))
+}
+
+/// One user's calendar year against another, with the monthly totals. Both
+/// sides cover a full calendar year so the months line up.
+pub async fn compare_years(
+    source: &ContributionSource,
+    username: &str,
+    other_year: &str,
+    options: &Options<'_>,
+) -> Result<Output> {
+    let Options {
+        config,
+        year,
+        colors,
+        ..
+    } = *options;
+    let (first, second) = tokio::join!(
+        source.fetch(username, year, true),
+        source.fetch(username, other_year, true)
+    );
+    let text = compare::render_years(
+        username,
+        (year, &first?),
+        (other_year, &second?),
+        config,
+        colors,
+    );
+    Ok(Output::new(text, Vec::new()))
+}
+
+/// Reads a team file with one username per line, ignoring `#` comments.
+pub fn read_team_file(path: &Path) -> Result<Vec<String>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read team file: {}", path.display()))?;
+    Ok(contents
+        .lines()
+        .map(|line| {
+            line.split('#')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        })
+        .collect())
+}
+
+/// The combined heatmap of `members` and the members of the `org_team`
+/// organization team, followed by a leaderboard. Without either, the
+/// `[team]` config section is used. Members that fail to load are skipped
+/// with a warning.
+pub async fn team(
+    source: &ContributionSource,
+    mut members: Vec<String>,
+    mut org_team: Option<(String, Option<String>)>,
+    options: &Options<'_>,
+) -> Result<Output> {
+    let Options {
+        config,
+        year,
+        year_specified,
+        colors,
+    } = *options;
+    // Fall back to the config only when nothing was given on the command line
+    if members.is_empty() && org_team.is_none() {
+        if let Some(team) = &config.team {
+            members.extend(team.members.iter().flatten().cloned());
+            org_team = team.org.clone().map(|org| (org, team.team.clone()));
+        }
+    }
+
+    if let Some((org, team)) = org_team {
+        let team = team.ok_or_else(|| {
+            FetchError::InvalidInput("--org needs --team <SLUG> to pick a team".to_string())
+        })?;
+        let octocrab = source.octocrab().ok_or_else(|| {
+            FetchError::MissingToken(
+                "looking up team members needs a GitHub token with the read:org scope".to_string(),
+            )
+        })?;
+        members.extend(fetch_team_members(octocrab, &org, &team).await?);
+    }
+
+    members.retain(|member| !member.is_empty());
+    let mut seen = HashSet::new();
+    members.retain(|member| seen.insert(member.to_lowercase()));
+    if members.is_empty() {
+        return Err(FetchError::InvalidInput(
+            "no team members given; use --users, --file, --org/--team or the [team] config section"
+                .to_string(),
+        )
+        .into());
+    }
+
+    let mut entries = Vec::new();
+    let mut warnings = Vec::new();
+    let mut first_error = None;
+    for (username, result) in team::fetch_team(source, &members, year, year_specified).await {
+        match result {
+            Ok(data) => entries.push(TeamEntry { username, data }),
+            Err(e) => {
+                warnings.push(format!("skipping {}: {}", username, e));
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if let (true, Some(e)) = (entries.is_empty(), first_error) {
+        return Err(e);
+    }
+    team::rank(&mut entries);
+
+    let combined = team::combine(&entries);
+    let graph = generate_contribution_graph(&combined.daily_contributions, year_specified, config);
+    let mut info = vec![
+        InfoLine::title("team", year),
+        InfoLine::field("Members", entries.len()),
+        InfoLine::field("Total contributions", combined.total),
+        InfoLine::field(
+            "Longest Team Streak",
+            format!("{} days", combined.longest_streak),
+        ),
+        InfoLine::field(
+            "Current Team Streak",
+            format!("{} days", combined.current_streak),
+        ),
+    ];
+    if combined.limited {
+        info.push(InfoLine::field("Data", "public profile only (limited)"));
+    }
+    let text = render_colored_graph(&graph, &info, config, colors)
+        + "\n"
+        + &team::render_leaderboard(&entries);
+    Ok(Output::new(text, warnings))
+}
+
+/// Commits by weekday and hour, read from the local `repo` or the `[punchcard]`
+/// one when given, otherwise found through the commit search.
+pub async fn punchcard(
+    host: &str,
+    username: &str,
+    repo: Option<&Path>,
+    options: &Options<'_>,
+) -> Result<Output> {
+    let Options {
+        config,
+        year,
+        year_specified,
+        colors,
+    } = *options;
+    let (first_day, last_day) = if year_specified {
+        year_bounds(parse_year(year)?)?
+    } else {
+        let today = timezone::today();
+        (today - Duration::days(364), today)
+    };
+    let (from, to) = day_range(first_day, last_day);
+
+    let mut warnings = Vec::new();
+    let repo = repo.or(config
+        .punchcard
+        .as_ref()
+        .and_then(|punchcard| punchcard.repo.as_deref().map(Path::new)));
+    let times = match repo {
+        Some(repo) => local_git::commit_times(repo, username, from, to)?,
+        None => {
+            let (source, warning) = connect(config, host)?;
+            warnings.extend(warning);
+            let public;
+            let octocrab = match source.octocrab() {
+                Some(octocrab) => octocrab,
+                None => {
+                    public = client_builder().build()?;
+                    &public
+                }
+            };
+            fetch_commit_times(octocrab, username, from, to).await?
+        }
+    };
+
+    let punchcard = punchcard::bucket(&times, timezone::zone());
+    let graph = generate_punchcard(&punchcard, config);
+    let mut info = vec![
+        InfoLine::title(username, year),
+        InfoLine::field("Commits", times.len()),
+    ];
+    if let Some((weekday, hour, count)) = punchcard::busiest_slot(&punchcard) {
+        info.push(InfoLine::field(
+            "Busiest Hour",
+            format!("{} {:02}:00 ({})", weekday, hour, count),
+        ));
+    }
+    info.push(InfoLine::field("Time Zone", timezone::zone()));
+    let text = render_colored_graph(&graph, &info, config, colors);
+    Ok(Output::new(text, warnings))
+}
+
+/// Everything contributed on `date`, with the commits of the local `repo`
+/// when given. Without a token only those are listed, and when GitHub doesn't
+/// know `username`, as with a git author name, they are listed with a warning.
+pub async fn day(
+    config: &Config,
+    host: &str,
+    username: &str,
+    date: NaiveDate,
+    repo: Option<&Path>,
+) -> Result<Output> {
+    let (source, warning) = connect(config, host)?;
+    let mut warnings: Vec<String> = warning.into_iter().collect();
+    let mut activity = match (source.octocrab(), repo) {
+        (Some(octocrab), None) => fetch_day_activity(octocrab, username, date)
+            .await
+            .map_err(|e| source.explain(e))?,
+        (Some(octocrab), Some(_)) => fetch_day_activity(octocrab, username, date)
+            .await
+            .unwrap_or_else(|e| {
+                warnings.push(format!(
+                    "listing local commits only, GitHub lookup failed: {}",
+                    source.explain(e)
+                ));
+                DayActivity::default()
+            }),
+        (None, Some(_)) => DayActivity::default(),
+        (None, None) => return Err(FetchError::MissingToken(
+            "listing a day's contributions needs a GitHub token, or --repo for local commits only"
+                .to_string(),
+        )
+        .into()),
+    };
+    if let Some(repo) = repo {
+        let (from, to) = day_range(date, date);
+        activity.local_commits = local_git::commits(repo, username, from, to)?;
+    }
+    Ok(Output::new(render_day(date, &activity), warnings))
+}
+
+/// Stores the token in the system keyring, or the config file with
+/// `plaintext` or when there's no keyring.
+pub fn add_token(
+    token: &str,
+    host: &str,
+    plaintext: bool,
+    config_path: Option<&Path>,
+) -> Result<Output> {
+    Ok(
+        match token_resolver::store_token(token, host, plaintext, config_path)? {
+            StoredIn::Keyring => Output::new(
+                format!("Token for {} stored in the system keyring.\n", host),
+                Vec::new(),
+            ),
+            StoredIn::ConfigFile(keyring_error) => Output::new(
+                "Token added successfully.\n".to_string(),
+                keyring_error
+                    .map(|e| {
+                        format!(
+                            "could not store token in the system keyring, so the config file holds it in plaintext: {:#}",
+                            e
+                        )
+                    })
+                    .into_iter()
+                    .collect(),
+            ),
+        },
+    )
+}
+
+/// Removes the stored token for `host`, noting when another one is still in
+/// effect.
+pub fn remove_token(config: &Config, host: &str, config_path: Option<&Path>) -> Result<Output> {
+    let removed = token_resolver::remove_token(config, host, config_path)?;
+    let mut text = match (removed.from_keyring, removed.from_config) {
+        (false, false) => format!("No stored token found for {}.\n", host),
+        (true, false) => format!("Removed token for {} from the system keyring.\n", host),
+        (false, true) => format!("Removed token for {} from the config file.\n", host),
+        (true, true) => format!(
+            "Removed token for {} from the system keyring and the config file.\n",
+            host
+        ),
+    };
+    if let Some(source) = removed.remaining {
+        text += &format!("Note: a token is still provided by the {}.\n", source);
+    }
+    Ok(Output::new(text, Vec::new()))
+}
+
+/// The token's source, login, scopes and rate limit, warning when it can't
+/// read private contributions.
+pub async fn token_status(config: &Config, host: &str) -> Result<Output> {
+    let token = token_resolver::resolve_token(config, host)?
+        .ok_or_else(|| FetchError::MissingToken(format!("no token found for {}", host)))?;
+    let mut text = format!("Host: {}\nSource: {}\n", host, token.source);
+
+    let octocrab = build_client(config.api_url(host).as_deref(), token.value)?;
+    let status = fetch_token_status(&octocrab).await?;
+    text += &format!("Login: {}\n", status.login);
+    text += &match &status.scopes {
+        Some(scopes) if scopes.is_empty() => "Scopes: (none)\n".to_string(),
+        Some(scopes) => format!("Scopes: {}\n", scopes.join(", ")),
+        None => "Scopes: not reported (fine-grained token)\n".to_string(),
+    };
+    text += &format!(
+        "Rate limit: {}/{} remaining, resets at {}\n",
+        status.rate_remaining, status.rate_limit, status.rate_reset_at
+    );
+
+    let mut warnings = Vec::new();
+    if status
+        .scopes
+        .as_ref()
+        .is_some_and(|scopes| !has_read_user_scope(scopes))
+    {
+        let fix = if token.source == TokenSource::GhHostsFile {
+            "Run 'gh auth refresh -s read:user' to add the scope to the gh CLI's token."
+        } else {
+            "Create a new token with read:user at https://github.com/settings/tokens."
+        };
+        warnings.push(format!(
+            "the token lacks the read:user scope, so private contributions can't be fetched. {}",
+            fix
+        ));
+    }
+    Ok(Output::new(text, warnings))
+}
+
+/// Parses a `--watch` interval like "90s", "5m" or "1h", raising intervals
+/// below the minimum with a warning.
+pub fn watch_interval(interval: &str) -> Result<(std::time::Duration, Option<String>)> {
+    let interval = watch::parse_interval(interval).ok_or_else(|| {
+        FetchError::InvalidInput(format!(
+            "'{}' is not an interval like 90s, 5m or 1h",
+            interval
+        ))
+    })?;
+    if interval < watch::MIN_INTERVAL {
+        let warning = format!(
+            "refreshing every {}s instead to spare the rate limit.",
+            watch::MIN_INTERVAL.as_secs()
+        );
+        return Ok((watch::MIN_INTERVAL, Some(warning)));
+    }
+    Ok((interval, None))
+}
+
+/// Redraws the graph and info panel every `interval` until interrupted,
+/// highlighting new contributions. Warnings show below the panel.
+pub async fn watch(
+    source: &ContributionSource,
+    username: &str,
+    interval: std::time::Duration,
+    options: &Options<'_>,
+) -> Result<()> {
+    let Options { config, year, .. } = *options;
+    let mut warnings = Vec::new();
+    let profile = profile(source, username, config, &mut warnings).await;
+    let info =
+        |data: &ContributionData| contribution_info(username, year, data, profile.as_ref(), config);
+    watch::run(source, username, interval, options, &warnings, info).await?;
+    Ok(())
+}
+
+/// The full-screen view, starting at `year` or the past year up to today.
+pub async fn tui(
+    source: &ContributionSource,
+    config: &Config,
+    username: String,
+    year: Option<i32>,
+) -> Result<()> {
+    tui::run(source, config, username, year).await
+}
+
+/// The profile when the info panel shows it, warning when it can't be had.
+async fn profile(
+    source: &ContributionSource,
+    username: &str,
+    config: &Config,
+    warnings: &mut Vec<String>,
+) -> Option<Profile> {
+    load_profile(source, username, config)
+        .await
+        .unwrap_or_else(|e| {
+            warnings.push(format!(
+                "could not fetch the profile of {}: {}",
+                username, e
+            ));
+            None
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_watch_intervals_are_raised_with_a_warning() {
+        let (interval, warning) = watch_interval("5m").unwrap();
+        assert_eq!(interval, std::time::Duration::from_secs(300));
+        assert!(warning.is_none());
+
+        let (interval, warning) = watch_interval("5").unwrap();
+        assert_eq!(interval, watch::MIN_INTERVAL);
+        assert!(warning.is_some());
+
+        let error = watch_interval("soon").unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<FetchError>(),
+            Some(FetchError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn team_files_skip_comments() {
+        let path = std::env::temp_dir().join(format!("gitfetch-team-{}.txt", std::process::id()));
+        fs::write(&path, "alice\n# reviewers\nbob  # on leave\n\n").unwrap();
+        let members = read_team_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(members.unwrap(), ["alice", "", "bob", ""]);
+    }
+}
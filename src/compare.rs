@@ -0,0 +1,254 @@
+use crate::config_manager::Config;
+use crate::contribution_analyzer::{monthly_totals, ContributionData};
+use crate::graph_generator::{
+    generate_contribution_graph, generate_diverging_graph, render_colored_graph,
+};
+use crate::info::InfoLine;
+use crate::layout::{self, GAP};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+fn active_days(data: &ContributionData) -> u32 {
+    data.daily_contributions
+        .iter()
+        .filter(|&&(_, count)| count > 0)
+        .count() as u32
+}
+
+/// Renders a table of both users' stats with the first user's lead over the
+/// second in the last column.
+pub fn render_stats_diff(
+    first_name: &str,
+    first: &ContributionData,
+    second_name: &str,
+    second: &ContributionData,
+) -> String {
+    let rows = [
+        ("Total contributions", first.total, second.total),
+        (
+            "Longest streak",
+            first.longest_streak,
+            second.longest_streak,
+        ),
+        (
+            "Current streak",
+            first.current_streak,
+            second.current_streak,
+        ),
+        ("Active days", active_days(first), active_days(second)),
+        (
+            "Most in a day",
+            first.max_contributions,
+            second.max_contributions,
+        ),
+    ];
+
+    let label_width = rows
+        .iter()
+        .map(|(label, _, _)| label.len())
+        .max()
+        .unwrap_or(0);
+    let first_width = first_name.chars().count().max(6);
+    let second_width = second_name.chars().count().max(6);
+
+    let mut table = format!(
+        "{:<label_width$}  {:>first_width$}  {:>second_width$}  {:>6}\n",
+        "", first_name, second_name, "diff"
+    );
+    for (label, a, b) in rows {
+        let diff = i64::from(a) - i64::from(b);
+        table += &format!(
+            "{:<label_width$}  {:>first_width$}  {:>second_width$}  {:>+6}\n",
+            label, a, b, diff
+        );
+    }
+    table
+}
+
+/// Renders per-month totals side by side, with the first column's lead over
+/// the second in the last column.
+pub fn render_monthly_diff(
+    first_label: &str,
+    first: &ContributionData,
+    second_label: &str,
+    second: &ContributionData,
+) -> String {
+    let first_totals = monthly_totals(&first.daily_contributions);
+    let second_totals = monthly_totals(&second.daily_contributions);
+    let first_width = first_label.chars().count().max(6);
+    let second_width = second_label.chars().count().max(6);
+
+    let mut table = format!(
+        "{:<5}  {:>first_width$}  {:>second_width$}  {:>6}\n",
+        "Month", first_label, second_label, "diff"
+    );
+    for (month, (a, b)) in MONTHS.iter().zip(first_totals.iter().zip(second_totals)) {
+        let diff = i64::from(*a) - i64::from(b);
+        table += &format!(
+            "{:<5}  {:>first_width$}  {:>second_width$}  {:>+6}\n",
+            month, a, b, diff
+        );
+    }
+    table
+}
+
+/// Both users' graphs, side by side when they fit in `width` columns and one
+/// above the other otherwise, followed by the stats table.
+pub fn render_side_by_side(
+    (first_name, first): (&str, &ContributionData),
+    (second_name, second): (&str, &ContributionData),
+    year: &str,
+    year_specified: bool,
+    width: Option<usize>,
+    config: &Config,
+    colors: bool,
+) -> String {
+    let [first_graph, second_graph] =
+        [(first_name, first), (second_name, second)].map(|(name, data)| {
+            let graph =
+                generate_contribution_graph(&data.daily_contributions, year_specified, config);
+            render_colored_graph(&graph, &[InfoLine::title(name, year)], config, colors)
+                .lines()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        });
+    let graph_width = |lines: &[String]| {
+        lines
+            .iter()
+            .map(|line| layout::visible_width(line))
+            .max()
+            .unwrap_or(0)
+    };
+    let fits = width
+        .is_some_and(|width| graph_width(&first_graph) + GAP + graph_width(&second_graph) <= width);
+
+    let mut out = if fits {
+        layout::beside(&first_graph, &second_graph) + "\n"
+    } else {
+        [first_graph, second_graph]
+            .iter()
+            .map(|graph| {
+                graph
+                    .iter()
+                    .map(|line| format!("{}\n", line))
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect()
+    };
+    out += &render_stats_diff(first_name, first, second_name, second);
+    out
+}
+
+/// One graph where each day takes the color of whoever contributed more,
+/// followed by the stats table.
+pub fn render_diverging(
+    (first_name, first): (&str, &ContributionData),
+    (second_name, second): (&str, &ContributionData),
+    year: &str,
+    year_specified: bool,
+    config: &Config,
+    colors: bool,
+) -> String {
+    let mut out = generate_diverging_graph(
+        &first.daily_contributions,
+        &second.daily_contributions,
+        (first_name, second_name),
+        year_specified,
+        config,
+        colors,
+    );
+    out += "\n";
+    let title = InfoLine::title(format!("{} vs {}", first_name, second_name), year);
+    out += &render_colored_graph("", &[title], config, colors);
+    out += "\n";
+    out += &render_stats_diff(first_name, first, second_name, second);
+    out
+}
+
+/// One user's graphs for two calendar years, one above the other, followed by
+/// the stats and the monthly totals of both.
+pub fn render_years(
+    username: &str,
+    (first_year, first): (&str, &ContributionData),
+    (second_year, second): (&str, &ContributionData),
+    config: &Config,
+    colors: bool,
+) -> String {
+    let mut out = String::new();
+    for (year, data) in [(first_year, first), (second_year, second)] {
+        let graph = generate_contribution_graph(&data.daily_contributions, true, config);
+        out += &render_colored_graph(&graph, &[InfoLine::title(username, year)], config, colors);
+        out += "\n";
+    }
+    out += &render_stats_diff(first_year, first, second_year, second);
+    out += "\n";
+    out += &render_monthly_diff(first_year, first, second_year, second);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contribution_analyzer::summarize_contributions;
+    use chrono::NaiveDate;
+
+    fn week(count: u32) -> ContributionData {
+        let days = (1..=7)
+            .map(|day| (NaiveDate::from_ymd_opt(2024, 1, day).unwrap(), count))
+            .collect();
+        summarize_contributions(7 * count, days)
+    }
+
+    #[test]
+    fn graphs_go_side_by_side_only_when_they_fit() {
+        let (alice, bob) = (week(1), week(2));
+        let render = |width| {
+            render_side_by_side(
+                ("alice", &alice),
+                ("bob", &bob),
+                "2024",
+                true,
+                width,
+                &Config::default(),
+                false,
+            )
+        };
+        let title_line =
+            |output: &str, title: &str| output.lines().position(|line| line.contains(title));
+
+        let wide = render(Some(200));
+        assert!(title_line(&wide, "alice@2024").is_some());
+        assert_eq!(
+            title_line(&wide, "alice@2024"),
+            title_line(&wide, "bob@2024")
+        );
+        assert!(wide.ends_with(&render_stats_diff("alice", &alice, "bob", &bob)));
+
+        for narrow in [render(Some(20)), render(None)] {
+            assert!(title_line(&narrow, "alice@2024") < title_line(&narrow, "bob@2024"));
+        }
+    }
+
+    #[test]
+    fn monthly_diffs_show_empty_months_and_the_lead_of_the_first() {
+        let date = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        let this_year = summarize_contributions(7, vec![(date(1, 3), 5), (date(3, 9), 2)]);
+        let last_year = summarize_contributions(7, vec![(date(1, 4), 3), (date(2, 1), 4)]);
+        assert_eq!(
+            monthly_totals(&this_year.daily_contributions)[..4],
+            [5, 0, 2, 0]
+        );
+
+        let table = render_monthly_diff("2024", &this_year, "2023", &last_year);
+        let rows: Vec<&str> = table.lines().collect();
+        assert_eq!(rows.len(), 13);
+        assert_eq!(rows[0], "Month    2024    2023    diff");
+        assert_eq!(rows[1], "Jan         5       3      +2");
+        assert_eq!(rows[2], "Feb         0       4      -4");
+        assert_eq!(rows[3], "Mar         2       0      +2");
+        assert_eq!(rows[12], "Dec         0       0      +0");
+    }
+}
@@ -1,14 +1,177 @@
 use anyhow::{Context, Result};
+use chrono::Weekday;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+const SYSTEM_CONFIG_PATH: &str = "/etc/gitfetch/config.toml";
+pub const DEFAULT_HOST: &str = "github.com";
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
     pub github_token: Option<String>,
+    pub token_command: Option<String>,
+    pub hosts: Option<BTreeMap<String, HostConfig>>,
+    /// IANA name or UTC offset that days are counted in; defaults to the
+    /// system's time zone.
+    pub timezone: Option<String>,
+    pub week_start: Option<WeekStart>,
+    pub orientation: Option<Orientation>,
     pub graph_colors: Option<GraphColors>,
     pub text_colors: Option<TextColors>,
     pub graph_data: Option<GraphData>,
+    pub team: Option<TeamConfig>,
+    pub compare_colors: Option<CompareColors>,
+    pub stats: Option<StatsConfig>,
+    pub punchcard: Option<PunchcardConfig>,
+    pub line: Option<LineConfig>,
+    pub info: Option<InfoConfig>,
+    pub avatar: Option<AvatarConfig>,
+    pub logo: Option<LogoConfig>,
+}
+
+/// The weekday in the top row of the graphs.
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Sunday,
+    Monday,
+}
+
+impl WeekStart {
+    pub fn weekday(self) -> Weekday {
+        match self {
+            WeekStart::Sunday => Weekday::Sun,
+            WeekStart::Monday => Weekday::Mon,
+        }
+    }
+}
+
+/// How the graphs are laid out: weeks as columns (like GitHub), or as rows
+/// for tall, narrow terminals.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    #[default]
+    Horizontal,
+    Vertical,
+}
+
+/// Settings for a GitHub Enterprise instance, keyed by host name under `[hosts]`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct HostConfig {
+    pub api_url: Option<String>,
+    pub token: Option<String>,
+    pub token_command: Option<String>,
+}
+
+/// Default members for `gitfetch team`: an explicit list and/or an
+/// organization team to look up.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct TeamConfig {
+    pub members: Option<Vec<String>>,
+    pub org: Option<String>,
+    pub team: Option<String>,
+}
+
+/// The one-line summary of `--format line` and `--format waybar`. Missing keys
+/// keep their default.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LineConfig {
+    pub template: String,
+    /// How many days, ending today, the `{sparkline}` placeholder covers.
+    pub sparkline_days: usize,
+}
+
+impl Default for LineConfig {
+    fn default() -> Self {
+        LineConfig {
+            template: "{current_streak}d streak · {week} this week {sparkline}".to_string(),
+            sparkline_days: 14,
+        }
+    }
+}
+
+/// Which fields the info panel shows and how. Missing keys keep their
+/// default.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct InfoConfig {
+    /// Field names in display order; `None` shows the basics plus the stats
+    /// enabled under `[stats]`.
+    pub fields: Option<Vec<String>>,
+    /// Replacement labels by field name.
+    pub labels: BTreeMap<String, String>,
+    /// Between a field's label and its value.
+    pub separator: String,
+    /// Between the username and the year in the title.
+    pub title_separator: String,
+}
+
+impl Default for InfoConfig {
+    fn default() -> Self {
+        InfoConfig {
+            fields: None,
+            labels: BTreeMap::new(),
+            separator: ": ".to_string(),
+            title_separator: "@".to_string(),
+        }
+    }
+}
+
+/// How the avatar is drawn. `Auto` picks a graphics protocol the terminal
+/// is known to support, falling back to half blocks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AvatarProtocol {
+    #[default]
+    Auto,
+    Blocks,
+    Kitty,
+    Iterm,
+    Sixel,
+}
+
+/// The user's avatar beside the info panel. Missing keys keep their default.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AvatarConfig {
+    pub enabled: bool,
+    pub protocol: AvatarProtocol,
+    /// Width in terminal columns.
+    pub width: u16,
+    /// How long a downloaded avatar is reused before fetching it again.
+    pub cache_days: u64,
+}
+
+impl Default for AvatarConfig {
+    fn default() -> Self {
+        AvatarConfig {
+            enabled: false,
+            protocol: AvatarProtocol::Auto,
+            width: 20,
+            cache_days: 7,
+        }
+    }
+}
+
+/// An ASCII-art logo beside the info panel, used when no avatar is shown.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct LogoConfig {
+    /// One of the built-in logos: github, gitlab or git.
+    pub name: Option<String>,
+    /// A text file with custom art, taking precedence over `name`.
+    pub path: Option<String>,
+}
+
+/// Defaults for `gitfetch punchcard`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct PunchcardConfig {
+    /// Local git repository to read commits from instead of GitHub.
+    pub repo: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -19,6 +182,48 @@ pub struct GraphColors {
     pub level4: String,
 }
 
+/// Which extended stats appear in the info panel. Missing keys keep their
+/// default.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StatsConfig {
+    pub active_days: bool,
+    pub average_per_active_day: bool,
+    pub median: bool,
+    pub busiest_weekday: bool,
+    pub busiest_month: bool,
+    pub longest_gap: bool,
+    pub weekend_ratio: bool,
+    pub rolling_7_day_average: bool,
+    pub rolling_30_day_average: bool,
+    /// Fetch earlier years so streaks running into the range count in full.
+    pub cross_year_streaks: bool,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        StatsConfig {
+            active_days: true,
+            average_per_active_day: false,
+            median: false,
+            busiest_weekday: true,
+            busiest_month: false,
+            longest_gap: false,
+            weekend_ratio: false,
+            rolling_7_day_average: false,
+            rolling_30_day_average: false,
+            cross_year_streaks: true,
+        }
+    }
+}
+
+/// Colors for the two users of `--compare --diverging`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CompareColors {
+    pub first: String,
+    pub second: String,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TextColors {
     pub info_color: String,
@@ -32,6 +237,11 @@ impl Default for Config {
     fn default() -> Self {
         Config {
             github_token: None,
+            token_command: None,
+            hosts: None,
+            timezone: None,
+            week_start: None,
+            orientation: None,
             graph_colors: Some(GraphColors {
                 level1: "13,68,41".to_string(),
                 level2: "1,108,49".to_string(),
@@ -44,60 +254,330 @@ impl Default for Config {
             graph_data: Some(GraphData {
                 percentiles: [0, 30, 60, 90],
             }),
+            team: None,
+            compare_colors: Some(CompareColors {
+                first: "56,139,253".to_string(),
+                second: "219,109,40".to_string(),
+            }),
+            stats: Some(StatsConfig::default()),
+            punchcard: None,
+            line: Some(LineConfig::default()),
+            info: Some(InfoConfig::default()),
+            avatar: Some(AvatarConfig::default()),
+            logo: None,
         }
     }
 }
 
-fn get_config_path() -> PathBuf {
-    let home = std::env::var("HOME").expect("HOME environment variable not set");
-    PathBuf::from(home)
-        .join(".config")
-        .join("gitfetch")
-        .join("config.toml")
-}
+impl Config {
+    pub fn host(&self, host: &str) -> Option<&HostConfig> {
+        self.hosts.as_ref()?.get(host)
+    }
 
-pub fn read_config() -> Result<Config> {
-    let config_path = get_config_path();
-    let default_config = Config::default();
-    if config_path.exists() {
-        let config_str = fs::read_to_string(&config_path)
-            .with_context(|| format!("Failed to read config file: {:?}", config_path))?;
-        let mut config: Config = toml::from_str(&config_str)
-            .with_context(|| format!("Failed to parse config file: {:?}", config_path))?;
-
-        // Merge with default config
-        if config.graph_colors.is_none() {
-            config.graph_colors = default_config.graph_colors;
+    /// The API base for `host`; `None` means the octocrab default, api.github.com.
+    pub fn api_url(&self, host: &str) -> Option<String> {
+        if host == DEFAULT_HOST {
+            return None;
+        }
+        Some(
+            self.host(host)
+                .and_then(|host| host.api_url.clone())
+                .unwrap_or_else(|| format!("https://{}/api", host)),
+        )
+    }
+
+    /// The plaintext token stored in the config file for `host`.
+    pub fn plaintext_token(&self, host: &str) -> Option<&str> {
+        if host == DEFAULT_HOST {
+            self.github_token.as_deref()
+        } else {
+            self.host(host)?.token.as_deref()
         }
-        if config.text_colors.is_none() {
-            config.text_colors = default_config.text_colors;
+    }
+
+    pub fn token_command(&self, host: &str) -> Option<&str> {
+        if host == DEFAULT_HOST {
+            self.token_command.as_deref()
+        } else {
+            self.host(host)?.token_command.as_deref()
         }
-        if config.graph_data.is_none() {
-            config.graph_data = default_config.graph_data;
+    }
+}
+
+/// Resolves the user config file, in order of precedence: the `--config` flag,
+/// the `GITFETCH_CONFIG` environment variable, `$XDG_CONFIG_HOME/gitfetch` and
+/// finally `$HOME/.config/gitfetch`.
+pub fn get_config_path(override_path: Option<&Path>) -> Result<PathBuf> {
+    config_path_from(override_path, |key| std::env::var(key).ok()).map(|(path, _)| path)
+}
+
+/// `get_config_path` with the environment passed in, also telling whether the
+/// path was chosen with `--config` or `GITFETCH_CONFIG` rather than defaulted.
+fn config_path_from(
+    override_path: Option<&Path>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<(PathBuf, bool)> {
+    let env = |key: &str| env(key).filter(|value| !value.is_empty());
+    if let Some(path) = override_path {
+        return Ok((path.to_path_buf(), true));
+    }
+    if let Some(path) = env("GITFETCH_CONFIG") {
+        return Ok((PathBuf::from(path), true));
+    }
+
+    // The XDG spec says relative paths must be ignored
+    let config_home = env("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    match config_home {
+        Some(dir) => Ok((dir.join("gitfetch").join("config.toml"), false)),
+        None => anyhow::bail!(
+            "Could not locate the config directory: neither XDG_CONFIG_HOME nor HOME is set. \
+             Use --config <PATH> or GITFETCH_CONFIG to choose a config file"
+        ),
+    }
+}
+
+/// Where downloaded files like avatars are kept, following the XDG spec like
+/// the config path.
+pub fn get_cache_dir() -> Option<PathBuf> {
+    non_empty_env("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| non_empty_env("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|dir| dir.join("gitfetch"))
+}
+
+fn non_empty_env(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|value| !value.is_empty())
+}
+
+fn read_layer(path: &Path) -> Result<Option<toml::Table>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let config_str = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {:?}", path))?;
+    let table = toml::from_str(&config_str)
+        .with_context(|| format!("Failed to parse config file: {:?}", path))?;
+    Ok(Some(table))
+}
+
+/// Recursively merges `overlay` into `base`; tables are merged key by key and
+/// any other value in `overlay` replaces the one in `base`.
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_tables(base_table, overlay_table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
         }
+    }
+}
 
-        Ok(config)
-    } else {
-        Ok(Config::default())
+/// Reads the system-wide config with the user config layered on top of it.
+/// Only the default user config may be missing; a path chosen with
+/// `--config` or `GITFETCH_CONFIG` must exist.
+pub fn read_config(override_path: Option<&Path>) -> Result<Config> {
+    let (config_path, chosen) = config_path_from(override_path, |key| std::env::var(key).ok())?;
+    if chosen && !config_path.exists() {
+        anyhow::bail!("Config file not found: {:?}", config_path);
+    }
+    let default_config = Config::default();
+
+    let mut merged = read_layer(Path::new(SYSTEM_CONFIG_PATH))?.unwrap_or_default();
+    if let Some(user) = read_layer(&config_path)? {
+        merge_tables(&mut merged, user);
+    }
+
+    let mut config: Config = merged
+        .try_into()
+        .with_context(|| format!("Failed to parse config file: {:?}", config_path))?;
+
+    // Merge with default config
+    if config.graph_colors.is_none() {
+        config.graph_colors = default_config.graph_colors;
+    }
+    if config.text_colors.is_none() {
+        config.text_colors = default_config.text_colors;
+    }
+    if config.graph_data.is_none() {
+        config.graph_data = default_config.graph_data;
+    }
+    if config.compare_colors.is_none() {
+        config.compare_colors = default_config.compare_colors;
+    }
+    if config.stats.is_none() {
+        config.stats = default_config.stats;
+    }
+    if config.line.is_none() {
+        config.line = default_config.line;
+    }
+    if config.info.is_none() {
+        config.info = default_config.info;
+    }
+    if config.avatar.is_none() {
+        config.avatar = default_config.avatar;
     }
+
+    Ok(config)
+}
+
+/// Reads only the user layer, so that writing it back never copies the
+/// system-wide defaults into the user's file.
+fn read_user_config(config_path: &Path) -> Result<Config> {
+    let table = read_layer(config_path)?.unwrap_or_default();
+    table
+        .try_into()
+        .with_context(|| format!("Failed to parse config file: {:?}", config_path))
 }
 
-fn save_config(config: &Config) -> Result<()> {
-    let config_path = get_config_path();
-    let config_dir = config_path.parent().unwrap();
-    fs::create_dir_all(config_dir)
-        .with_context(|| format!("Failed to create config directory: {:?}", config_dir))?;
+fn save_config(config: &Config, config_path: &Path) -> Result<()> {
+    if let Some(config_dir) = config_path.parent() {
+        fs::create_dir_all(config_dir)
+            .with_context(|| format!("Failed to create config directory: {:?}", config_dir))?;
+    }
     let config_str =
         toml::to_string_pretty(config).with_context(|| "Failed to serialize config")?;
-    fs::write(&config_path, config_str)
+    fs::write(config_path, config_str)
         .with_context(|| format!("Failed to write config file: {:?}", config_path))?;
     Ok(())
 }
 
-pub fn add_token(token: &str) -> Result<()> {
-    let mut config = read_config()?;
-    config.github_token = Some(token.to_string());
-    save_config(&config)?;
-    println!("Token added successfully.");
-    Ok(())
+pub fn add_token(token: &str, host: &str, override_path: Option<&Path>) -> Result<()> {
+    let config_path = get_config_path(override_path)?;
+    let mut config = read_user_config(&config_path)?;
+    if host == DEFAULT_HOST {
+        config.github_token = Some(token.to_string());
+    } else {
+        config
+            .hosts
+            .get_or_insert_with(BTreeMap::new)
+            .entry(host.to_string())
+            .or_default()
+            .token = Some(token.to_string());
+    }
+    save_config(&config, &config_path)
+}
+
+/// Removes the plaintext token for `host` from the user config, returning
+/// whether there was one.
+pub fn remove_token(host: &str, override_path: Option<&Path>) -> Result<bool> {
+    let config_path = get_config_path(override_path)?;
+    let mut config = read_user_config(&config_path)?;
+    let removed = if host == DEFAULT_HOST {
+        config.github_token.take()
+    } else {
+        config
+            .hosts
+            .as_mut()
+            .and_then(|hosts| hosts.get_mut(host))
+            .and_then(|host| host.token.take())
+    };
+    if removed.is_some() {
+        save_config(&config, &config_path)?;
+    }
+    Ok(removed.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config_path(override_path: Option<&str>, env: &[(&str, &str)]) -> (PathBuf, bool) {
+        let env: HashMap<&str, &str> = env.iter().copied().collect();
+        config_path_from(override_path.map(Path::new), |key| {
+            env.get(key).map(|value| value.to_string())
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn config_paths_are_resolved_in_order_of_precedence() {
+        let env = [
+            ("GITFETCH_CONFIG", "/env/config.toml"),
+            ("XDG_CONFIG_HOME", "/xdg"),
+            ("HOME", "/home/jane"),
+        ];
+        assert_eq!(
+            config_path(Some("flag.toml"), &env),
+            (PathBuf::from("flag.toml"), true)
+        );
+        assert_eq!(
+            config_path(None, &env),
+            (PathBuf::from("/env/config.toml"), true)
+        );
+        assert_eq!(
+            config_path(None, &env[1..]),
+            (PathBuf::from("/xdg/gitfetch/config.toml"), false)
+        );
+        // Empty and relative values are skipped
+        assert_eq!(
+            config_path(
+                None,
+                &[
+                    ("GITFETCH_CONFIG", ""),
+                    ("XDG_CONFIG_HOME", "xdg"),
+                    ("HOME", "/home/jane")
+                ]
+            ),
+            (
+                PathBuf::from("/home/jane/.config/gitfetch/config.toml"),
+                false
+            )
+        );
+        assert!(config_path_from(None, |_| None).is_err());
+    }
+
+    #[test]
+    fn chosen_config_files_must_exist() {
+        let path = std::env::temp_dir().join("gitfetch-missing-config.toml");
+        let error = read_config(Some(&path)).unwrap_err();
+        assert!(error.to_string().starts_with("Config file not found"));
+    }
+
+    #[test]
+    fn tables_merge_key_by_key_and_other_values_replace() {
+        let mut base: toml::Table = toml::from_str(
+            r#"
+            orientation = "horizontal"
+            [graph_colors]
+            level_1 = "green"
+            level_2 = "blue"
+            [team]
+            members = ["alice", "bob"]
+            "#,
+        )
+        .unwrap();
+        let overlay: toml::Table = toml::from_str(
+            r#"
+            orientation = "vertical"
+            [graph_colors]
+            level_2 = "red"
+            [team]
+            members = ["carol"]
+            "#,
+        )
+        .unwrap();
+        merge_tables(&mut base, overlay);
+
+        let expected: toml::Table = toml::from_str(
+            r#"
+            orientation = "vertical"
+            [graph_colors]
+            level_1 = "green"
+            level_2 = "red"
+            [team]
+            members = ["carol"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(base, expected);
+    }
 }
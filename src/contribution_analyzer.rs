@@ -1,20 +1,224 @@
-use chrono::NaiveDate;
+pub mod streaks;
 
+use crate::timezone;
+use chrono::{Datelike, NaiveDate, Weekday};
+
+#[non_exhaustive]
 pub struct ContributionData {
     pub total: u32,
     pub longest_streak: u32,
     pub current_streak: u32,
     pub max_contributions: u32,
     pub daily_contributions: Vec<(NaiveDate, u32)>,
+    /// Set when the data comes from the public profile page rather than the
+    /// API, which omits private contributions the user hasn't chosen to show.
+    pub limited: bool,
+}
+
+impl ContributionData {
+    /// First and last day of the displayed range.
+    pub fn date_range(&self) -> Option<(NaiveDate, NaiveDate)> {
+        Some((
+            self.daily_contributions.first()?.0,
+            self.daily_contributions.last()?.0,
+        ))
+    }
+
+    /// Recomputes the streaks, counting `history` from before the displayed
+    /// range so that streaks crossing into it are measured in full.
+    pub fn update_streaks(&mut self, history: &[(NaiveDate, u32)]) {
+        let Some(range) = self.date_range() else {
+            self.longest_streak = 0;
+            self.current_streak = 0;
+            return;
+        };
+        let days: Vec<(NaiveDate, u32)> = history
+            .iter()
+            .chain(&self.daily_contributions)
+            .copied()
+            .collect();
+        let streaks = streaks::calculate_streaks(&days, range, timezone::today());
+        self.longest_streak = streaks.longest;
+        self.current_streak = streaks.current;
+    }
+}
+
+/// Builds `ContributionData` from daily counts in any order.
+pub fn summarize_contributions(
+    total: u32,
+    mut daily_contributions: Vec<(NaiveDate, u32)>,
+) -> ContributionData {
+    daily_contributions.sort_by_key(|&(date, _)| date);
+    daily_contributions.dedup_by_key(|&mut (date, _)| date);
+    let max_contributions = daily_contributions
+        .iter()
+        .map(|&(_, count)| count)
+        .max()
+        .unwrap_or(0);
+
+    let mut data = ContributionData {
+        total,
+        longest_streak: 0,
+        current_streak: 0,
+        max_contributions,
+        daily_contributions,
+        limited: false,
+    };
+    data.update_streaks(&[]);
+    data
+}
+
+#[non_exhaustive]
+pub struct ExtendedStats {
+    pub active_days: u32,
+    pub total_days: u32,
+    pub average_per_active_day: f64,
+    /// Median contributions on active days.
+    pub median: f64,
+    pub busiest_weekday: Option<(Weekday, u32)>,
+    /// Zero-based month and its total.
+    pub busiest_month: Option<(usize, u32)>,
+    /// Longest run of consecutive days without contributions.
+    pub longest_gap: u32,
+    /// Average per weekend day divided by average per weekday, `None` when
+    /// there were no weekday contributions.
+    pub weekend_ratio: Option<f64>,
+    pub rolling_7_day_average: f64,
+    pub rolling_30_day_average: f64,
+}
+
+impl ExtendedStats {
+    pub fn active_percentage(&self) -> f64 {
+        if self.total_days == 0 {
+            0.0
+        } else {
+            self.active_days as f64 * 100.0 / self.total_days as f64
+        }
+    }
+}
+
+/// Computes the optional info panel stats from chronologically ordered daily
+/// counts. Days after today aren't counted, so rolling averages cover the last
+/// days up to today, like the streaks.
+pub fn calculate_extended_stats(daily_contributions: &[(NaiveDate, u32)]) -> ExtendedStats {
+    extended_stats_until(daily_contributions, timezone::today())
+}
+
+fn extended_stats_until(
+    daily_contributions: &[(NaiveDate, u32)],
+    today: NaiveDate,
+) -> ExtendedStats {
+    let daily_contributions =
+        &daily_contributions[..daily_contributions.partition_point(|&(date, _)| date <= today)];
+    let mut active: Vec<u32> = daily_contributions
+        .iter()
+        .map(|&(_, count)| count)
+        .filter(|&count| count > 0)
+        .collect();
+    active.sort_unstable();
+    let active_total: u32 = active.iter().sum();
+
+    let median = match active.len() {
+        0 => 0.0,
+        len if len % 2 == 0 => (active[len / 2 - 1] + active[len / 2]) as f64 / 2.0,
+        len => active[len / 2] as f64,
+    };
+
+    let mut weekday_totals = [0u32; 7];
+    let (mut weekend_sum, mut weekend_days, mut weekday_sum, mut weekday_days) = (0, 0, 0, 0);
+    let mut longest_gap = 0;
+    let mut gap = 0;
+    for &(date, count) in daily_contributions {
+        weekday_totals[date.weekday().num_days_from_monday() as usize] += count;
+        if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            weekend_sum += count;
+            weekend_days += 1;
+        } else {
+            weekday_sum += count;
+            weekday_days += 1;
+        }
+
+        if count == 0 {
+            gap += 1;
+            longest_gap = longest_gap.max(gap);
+        } else {
+            gap = 0;
+        }
+    }
+
+    let busiest_weekday = weekday_totals
+        .iter()
+        .enumerate()
+        .filter(|&(_, &total)| total > 0)
+        .max_by_key(|&(index, &total)| (total, std::cmp::Reverse(index)))
+        .map(|(index, &total)| {
+            (
+                Weekday::try_from(index as u8).unwrap_or(Weekday::Mon),
+                total,
+            )
+        });
+    let busiest_month = monthly_totals(daily_contributions)
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, total)| total > 0)
+        .max_by_key(|&(month, total)| (total, std::cmp::Reverse(month)));
+
+    let weekend_ratio = (weekday_sum > 0 && weekend_days > 0).then(|| {
+        (weekend_sum as f64 / weekend_days as f64) / (weekday_sum as f64 / weekday_days as f64)
+    });
+
+    ExtendedStats {
+        active_days: active.len() as u32,
+        total_days: daily_contributions.len() as u32,
+        average_per_active_day: if active.is_empty() {
+            0.0
+        } else {
+            active_total as f64 / active.len() as f64
+        },
+        median,
+        busiest_weekday,
+        busiest_month,
+        longest_gap,
+        weekend_ratio,
+        rolling_7_day_average: rolling_average(daily_contributions, 7),
+        rolling_30_day_average: rolling_average(daily_contributions, 30),
+    }
+}
+
+fn rolling_average(daily_contributions: &[(NaiveDate, u32)], days: usize) -> f64 {
+    let window = &daily_contributions[daily_contributions.len().saturating_sub(days)..];
+    if window.is_empty() {
+        return 0.0;
+    }
+    window.iter().map(|&(_, count)| count).sum::<u32>() as f64 / window.len() as f64
+}
+
+/// Sums contributions per calendar month, January first.
+pub fn monthly_totals(daily_contributions: &[(NaiveDate, u32)]) -> [u32; 12] {
+    let mut totals = [0; 12];
+    for &(date, count) in daily_contributions {
+        totals[date.month0() as usize] += count;
+    }
+    totals
 }
 
 pub fn calculate_contribution_ranges(
     daily_contributions: &[(NaiveDate, u32)],
     percentiles: &[usize; 4],
 ) -> Vec<u32> {
-    let mut contributions: Vec<u32> = daily_contributions
-        .iter()
-        .map(|&(_, count)| count)
+    calculate_count_ranges(
+        daily_contributions.iter().map(|&(_, count)| count),
+        percentiles,
+    )
+}
+
+/// Level thresholds for any set of counts, e.g. the cells of a punchcard.
+pub fn calculate_count_ranges(
+    counts: impl IntoIterator<Item = u32>,
+    percentiles: &[usize; 4],
+) -> Vec<u32> {
+    let mut contributions: Vec<u32> = counts
+        .into_iter()
         .filter(|&count| count > 0) // Exclude days with zero contributions
         .collect();
 
@@ -75,3 +279,77 @@ pub fn calculate_contribution_ranges(
 
     ranges
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    /// Consecutive days from Monday, January 1st, 2024 with the given counts.
+    fn days(counts: &[u32]) -> Vec<(NaiveDate, u32)> {
+        date(1, 1).iter_days().zip(counts.iter().copied()).collect()
+    }
+
+    fn stats(counts: &[u32]) -> ExtendedStats {
+        extended_stats_until(&days(counts), date(12, 31))
+    }
+
+    #[test]
+    fn medians_and_gaps() {
+        let cases: [(&[u32], f64, u32); 5] = [
+            (&[], 0.0, 0),
+            (&[0, 0], 0.0, 2),
+            (&[0, 3, 1, 2], 2.0, 1),
+            (&[4, 1, 0, 0, 2, 3], 2.5, 2),
+            (&[1, 0, 0, 2, 0, 0, 0, 1], 1.0, 3),
+        ];
+        for (counts, median, longest_gap) in cases {
+            let stats = stats(counts);
+            assert_eq!(stats.median, median, "median of {:?}", counts);
+            assert_eq!(stats.longest_gap, longest_gap, "gap in {:?}", counts);
+        }
+    }
+
+    #[test]
+    fn weekend_ratios_and_busiest_weekdays() {
+        // Saturday and Sunday only, so there's nothing to compare them with
+        let weekend_only = stats(&[0, 0, 0, 0, 0, 2, 4]);
+        assert_eq!(weekend_only.weekend_ratio, None);
+        assert_eq!(weekend_only.busiest_weekday, Some((Weekday::Sun, 4)));
+
+        let both = stats(&[1, 1, 1, 1, 1, 2, 2]);
+        assert_eq!(both.weekend_ratio, Some(2.0));
+
+        // Ties go to the earlier weekday
+        let tied = stats(&[3, 0, 0, 3, 0, 0, 0]);
+        assert_eq!(tied.busiest_weekday, Some((Weekday::Mon, 3)));
+        assert_eq!(stats(&[0; 7]).busiest_weekday, None);
+    }
+
+    #[test]
+    fn rolling_averages_cover_the_last_days() {
+        let mut counts = vec![1; 33];
+        counts.extend([8; 7]);
+        let long = stats(&counts);
+        assert_eq!(long.rolling_7_day_average, 8.0);
+        assert_eq!(long.rolling_30_day_average, (23.0 + 7.0 * 8.0) / 30.0);
+
+        let short = stats(&[2, 4]);
+        assert_eq!(short.rolling_7_day_average, 3.0);
+        assert_eq!(short.rolling_30_day_average, 3.0);
+    }
+
+    #[test]
+    fn days_after_today_are_not_counted() {
+        let mut counts = vec![1; 10];
+        counts.extend([0; 356]);
+        let stats = extended_stats_until(&days(&counts), date(1, 10));
+        assert_eq!((stats.active_days, stats.total_days), (10, 10));
+        assert_eq!(stats.longest_gap, 0);
+        assert_eq!(stats.rolling_7_day_average, 1.0);
+        assert_eq!(stats.rolling_30_day_average, 1.0);
+    }
+}
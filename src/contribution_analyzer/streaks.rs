@@ -0,0 +1,196 @@
+use chrono::NaiveDate;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Streaks {
+    pub longest: u32,
+    pub current: u32,
+}
+
+/// Computes streaks over `daily_contributions`, which may be unsorted and
+/// have missing dates; a missing date counts as a day without contributions.
+///
+/// Only streaks overlapping `range` count towards the longest one, but they
+/// count in full, so days from before the range (such as the previous year)
+/// can be passed in to measure streaks crossing into it.
+///
+/// The current streak ends on `today`, or on the last day of `range` when
+/// that is earlier. A streak that ended the day before still counts, since
+/// there is time left to contribute today.
+pub fn calculate_streaks(
+    daily_contributions: &[(NaiveDate, u32)],
+    range: (NaiveDate, NaiveDate),
+    today: NaiveDate,
+) -> Streaks {
+    let active: BTreeSet<NaiveDate> = daily_contributions
+        .iter()
+        .filter(|&&(_, count)| count > 0)
+        .map(|&(date, _)| date)
+        .collect();
+
+    let mut longest = 0;
+    let mut run: Option<(NaiveDate, NaiveDate, u32)> = None;
+    for &date in &active {
+        run = match run {
+            Some((start, end, len)) if end.succ_opt() == Some(date) => Some((start, date, len + 1)),
+            finished => {
+                if let Some((start, end, len)) = finished {
+                    if overlaps((start, end), range) {
+                        longest = longest.max(len);
+                    }
+                }
+                Some((date, date, 1))
+            }
+        };
+    }
+    if let Some((start, end, len)) = run {
+        if overlaps((start, end), range) {
+            longest = longest.max(len);
+        }
+    }
+
+    let reference = today.min(range.1);
+    let anchor = if active.contains(&reference) {
+        Some(reference)
+    } else {
+        reference
+            .pred_opt()
+            .filter(|yesterday| active.contains(yesterday))
+    };
+    let current = anchor.map_or(0, |anchor| {
+        std::iter::successors(Some(anchor), |date| date.pred_opt())
+            .take_while(|date| active.contains(date))
+            .count() as u32
+    });
+
+    Streaks { longest, current }
+}
+
+fn overlaps((start, end): (NaiveDate, NaiveDate), range: (NaiveDate, NaiveDate)) -> bool {
+    start <= range.1 && end >= range.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// Consecutive days starting at `start` with the given counts.
+    fn days(start: NaiveDate, counts: &[u32]) -> Vec<(NaiveDate, u32)> {
+        start.iter_days().zip(counts.iter().copied()).collect()
+    }
+
+    #[test]
+    fn counts_longest_and_current() {
+        let daily = days(date(2024, 3, 1), &[1, 1, 0, 2, 3, 4, 0, 1, 1]);
+        let range = (date(2024, 3, 1), date(2024, 3, 9));
+        assert_eq!(
+            calculate_streaks(&daily, range, date(2024, 3, 9)),
+            Streaks {
+                longest: 3,
+                current: 2
+            }
+        );
+    }
+
+    #[test]
+    fn keeps_current_streak_alive_when_today_has_no_contributions_yet() {
+        let daily = days(date(2024, 3, 1), &[0, 1, 1, 1, 0]);
+        let range = (date(2024, 3, 1), date(2024, 3, 5));
+        assert_eq!(
+            calculate_streaks(&daily, range, date(2024, 3, 5)).current,
+            3
+        );
+    }
+
+    #[test]
+    fn breaks_current_streak_after_a_full_day_without_contributions() {
+        let daily = days(date(2024, 3, 1), &[1, 1, 1, 0, 0]);
+        let range = (date(2024, 3, 1), date(2024, 3, 5));
+        assert_eq!(
+            calculate_streaks(&daily, range, date(2024, 3, 5)).current,
+            0
+        );
+    }
+
+    #[test]
+    fn treats_missing_dates_as_gaps() {
+        let daily = vec![
+            (date(2024, 3, 1), 1),
+            (date(2024, 3, 2), 1),
+            (date(2024, 3, 4), 1),
+            (date(2024, 3, 5), 1),
+            (date(2024, 3, 6), 1),
+        ];
+        let range = (date(2024, 3, 1), date(2024, 3, 6));
+        assert_eq!(
+            calculate_streaks(&daily, range, date(2024, 3, 6)),
+            Streaks {
+                longest: 3,
+                current: 3
+            }
+        );
+    }
+
+    #[test]
+    fn does_not_depend_on_input_order() {
+        let mut daily = days(date(2024, 3, 1), &[1, 1, 0, 1, 1, 1]);
+        daily.reverse();
+        daily.swap(1, 4);
+        let range = (date(2024, 3, 1), date(2024, 3, 6));
+        assert_eq!(
+            calculate_streaks(&daily, range, date(2024, 3, 6)),
+            Streaks {
+                longest: 3,
+                current: 3
+            }
+        );
+    }
+
+    #[test]
+    fn counts_streaks_crossing_into_the_range_in_full() {
+        let mut daily = days(date(2023, 12, 29), &[1, 1, 1]);
+        daily.extend(days(date(2024, 1, 1), &[1, 1, 0, 0]));
+        let range = (date(2024, 1, 1), date(2024, 1, 4));
+        assert_eq!(
+            calculate_streaks(&daily, range, date(2024, 6, 1)).longest,
+            5
+        );
+    }
+
+    #[test]
+    fn ignores_streaks_entirely_outside_the_range() {
+        let mut daily = days(date(2023, 12, 1), &[1; 20]);
+        daily.extend(days(date(2024, 1, 1), &[0, 1, 1, 0]));
+        let range = (date(2024, 1, 1), date(2024, 1, 4));
+        assert_eq!(
+            calculate_streaks(&daily, range, date(2024, 1, 4)).longest,
+            2
+        );
+    }
+
+    #[test]
+    fn measures_current_streak_at_the_end_of_a_past_range() {
+        let daily = days(date(2023, 12, 28), &[0, 1, 1, 1]);
+        let range = (date(2023, 1, 1), date(2023, 12, 31));
+        assert_eq!(
+            calculate_streaks(&daily, range, date(2024, 5, 1)).current,
+            3
+        );
+    }
+
+    #[test]
+    fn handles_empty_input() {
+        let range = (date(2024, 1, 1), date(2024, 12, 31));
+        assert_eq!(
+            calculate_streaks(&[], range, date(2024, 5, 1)),
+            Streaks {
+                longest: 0,
+                current: 0
+            }
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::contribution_analyzer::ContributionData;
+use crate::errors::FetchError;
+use crate::github_client::{fetch_contributions, fetch_profile};
+use crate::profile::Profile;
+use crate::public_profile::fetch_public_contributions;
+use crate::timezone;
+use chrono::{Datelike, NaiveDate};
+use octocrab::Octocrab;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// How far back streaks are followed across year boundaries.
+const MAX_HISTORY_YEARS: usize = 10;
+
+/// Where contribution calendars come from: the GraphQL API when a token is
+/// available, otherwise the public profile page.
+#[derive(Clone)]
+pub enum Provider {
+    GitHub(Octocrab),
+    PublicProfile,
+}
+
+/// Daily counts of past years by lowercased username and year. Shared between
+/// clones, so `--watch` fetches each past year only once.
+type History = Arc<Mutex<HashMap<(String, i32), Vec<(NaiveDate, u32)>>>>;
+
+#[derive(Clone)]
+pub struct ContributionSource {
+    provider: Provider,
+    /// Fetch earlier years while a streak runs into the start of the range.
+    cross_year_streaks: bool,
+    /// Whether the API token was borrowed from the gh CLI.
+    token_from_gh: bool,
+    history: History,
+}
+
+impl ContributionSource {
+    pub fn new(provider: Provider, cross_year_streaks: bool) -> Self {
+        ContributionSource {
+            provider,
+            cross_year_streaks,
+            token_from_gh: false,
+            history: History::default(),
+        }
+    }
+
+    /// Notes that the token came from the gh CLI, so errors about it can say so.
+    pub(crate) fn token_from_gh(mut self, from_gh: bool) -> Self {
+        self.token_from_gh = from_gh;
+        self
+    }
+
+    /// Attributes a missing scope to the gh CLI when its token is in use.
+    pub(crate) fn explain(&self, error: FetchError) -> FetchError {
+        if self.token_from_gh {
+            error.blame_gh_token()
+        } else {
+            error
+        }
+    }
+
+    pub async fn fetch(
+        &self,
+        username: &str,
+        year: &str,
+        year_specified: bool,
+    ) -> Result<ContributionData, FetchError> {
+        let mut data = self
+            .fetch_range(username, year, year_specified)
+            .await
+            .map_err(|e| self.explain(e))?;
+        if self.cross_year_streaks {
+            self.extend_streaks(username, &mut data).await;
+        }
+        Ok(data)
+    }
+
+    /// The user's profile, or `None` when the provider has no API to ask.
+    pub async fn profile(&self, username: &str) -> Result<Option<Profile>, FetchError> {
+        match &self.provider {
+            Provider::GitHub(octocrab) => fetch_profile(octocrab, username)
+                .await
+                .map(Some)
+                .map_err(|e| self.explain(e)),
+            Provider::PublicProfile => Ok(None),
+        }
+    }
+
+    pub fn octocrab(&self) -> Option<&Octocrab> {
+        match &self.provider {
+            Provider::GitHub(octocrab) => Some(octocrab),
+            Provider::PublicProfile => None,
+        }
+    }
+
+    async fn fetch_range(
+        &self,
+        username: &str,
+        year: &str,
+        year_specified: bool,
+    ) -> Result<ContributionData, FetchError> {
+        match &self.provider {
+            Provider::GitHub(octocrab) => {
+                fetch_contributions(username, year, year_specified, octocrab.clone()).await
+            }
+            Provider::PublicProfile => {
+                fetch_public_contributions(username, year, year_specified).await
+            }
+        }
+    }
+
+    /// Walks back a year at a time while every day before the range has been
+    /// active, so a streak that started in an earlier year counts in full.
+    /// Failing to fetch history only costs accuracy, so errors end the walk.
+    async fn extend_streaks(&self, username: &str, data: &mut ContributionData) {
+        let mut history: Vec<(NaiveDate, u32)> = Vec::new();
+        for _ in 0..MAX_HISTORY_YEARS {
+            let unbroken = data
+                .daily_contributions
+                .first()
+                .is_some_and(|&(_, count)| count > 0)
+                && history.iter().all(|&(_, count)| count > 0);
+            if !unbroken {
+                break;
+            }
+            let Some(&(earliest, _)) = history.first().or(data.daily_contributions.first()) else {
+                break;
+            };
+            let Some(previous_day) = earliest.pred_opt() else {
+                break;
+            };
+
+            let Ok(previous) = self.past_year(username, previous_day.year()).await else {
+                break;
+            };
+            let mut earlier: Vec<(NaiveDate, u32)> = previous
+                .into_iter()
+                .filter(|&(date, _)| date < earliest)
+                .collect();
+            if earlier.is_empty() {
+                break;
+            }
+            earlier.append(&mut history);
+            history = earlier;
+        }
+
+        if !history.is_empty() {
+            data.update_streaks(&history);
+        }
+    }
+
+    /// The daily counts of `year`, from the cache once the year is over.
+    async fn past_year(
+        &self,
+        username: &str,
+        year: i32,
+    ) -> Result<Vec<(NaiveDate, u32)>, FetchError> {
+        let key = (username.to_lowercase(), year);
+        if let Some(days) = self.history.lock().unwrap().get(&key) {
+            return Ok(days.clone());
+        }
+        let days = self
+            .fetch_range(username, &year.to_string(), true)
+            .await?
+            .daily_contributions;
+        if year < timezone::today().year() {
+            self.history.lock().unwrap().insert(key, days.clone());
+        }
+        Ok(days)
+    }
+}
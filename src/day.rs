@@ -0,0 +1,95 @@
+use crate::timezone;
+use chrono::{DateTime, FixedOffset, NaiveDate};
+
+/// A pull request or issue someone opened or reviewed.
+#[non_exhaustive]
+pub struct ActivityItem {
+    pub repository: String,
+    pub number: u64,
+    pub title: String,
+}
+
+/// A commit found through the commit search.
+#[non_exhaustive]
+pub struct Commit {
+    pub repository: String,
+    pub time: DateTime<FixedOffset>,
+    pub hash: String,
+    pub subject: String,
+}
+
+/// A commit read from a local repository.
+#[non_exhaustive]
+pub struct LocalCommit {
+    pub time: DateTime<FixedOffset>,
+    pub hash: String,
+    pub subject: String,
+}
+
+/// Everything contributed on one day.
+#[derive(Default)]
+#[non_exhaustive]
+pub struct DayActivity {
+    pub commits: Vec<Commit>,
+    pub pull_requests: Vec<ActivityItem>,
+    pub issues: Vec<ActivityItem>,
+    pub reviews: Vec<ActivityItem>,
+    pub local_commits: Vec<LocalCommit>,
+}
+
+impl DayActivity {
+    pub fn is_empty(&self) -> bool {
+        self.commits.is_empty()
+            && self.pull_requests.is_empty()
+            && self.issues.is_empty()
+            && self.reviews.is_empty()
+            && self.local_commits.is_empty()
+    }
+}
+
+/// Lists the day's activity grouped by kind, leaving out empty groups.
+pub fn render_day(date: NaiveDate, activity: &DayActivity) -> String {
+    let mut out = format!("{}\n", date.format("%A, %B %-d, %Y"));
+    if activity.is_empty() {
+        out += "\nNo contributions.\n";
+        return out;
+    }
+
+    if !activity.commits.is_empty() {
+        out += &format!("\nCommits ({})\n", activity.commits.len());
+        for commit in &activity.commits {
+            out += &format!(
+                "  {} {} {} {}\n",
+                timezone::zone().localize(&commit.time).format("%H:%M"),
+                commit.repository,
+                commit.hash,
+                commit.subject
+            );
+        }
+    }
+    for (heading, items) in [
+        ("Pull requests", &activity.pull_requests),
+        ("Issues", &activity.issues),
+        ("Reviews", &activity.reviews),
+    ] {
+        if items.is_empty() {
+            continue;
+        }
+        out += &format!("\n{} ({})\n", heading, items.len());
+        for item in items {
+            out += &format!("  {}#{} {}\n", item.repository, item.number, item.title);
+        }
+    }
+    if !activity.local_commits.is_empty() {
+        out += &format!("\nLocal commits ({})\n", activity.local_commits.len());
+        for commit in &activity.local_commits {
+            out += &format!(
+                "  {} {} {}\n",
+                timezone::zone().localize(&commit.time).format("%H:%M"),
+                commit.hash,
+                commit.subject
+            );
+        }
+    }
+    out
+}
@@ -1,16 +1,215 @@
+use crate::timezone;
+use chrono::{DateTime, Utc};
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum FetchError {
-    #[error("Failed to parse year: {0}")]
-    YearParseError(#[from] std::num::ParseIntError),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
 
-    #[error("Failed to create date")]
-    DateCreationError,
+    #[error("User '{0}' not found")]
+    UserNotFound(String),
+
+    #[error("Bad credentials: GitHub rejected the token")]
+    BadCredentials,
+
+    #[error("GitHub API rate limit exceeded{}", format_reset(.reset_at))]
+    RateLimited { reset_at: Option<DateTime<Utc>> },
+
+    #[error("Network error: {0}")]
+    Network(String),
+
+    #[error("GitHub GraphQL error: {}", format_graphql_errors(.0))]
+    GraphQl(Vec<GraphqlError>),
+
+    #[error("Missing token: {0}")]
+    MissingToken(String),
+
+    #[error("{} lacks the read:user scope (granted: {})", token_name(*.from_gh), .granted.join(", "))]
+    MissingScope { granted: Vec<String>, from_gh: bool },
 
     #[error("GitHub API error: {0}")]
-    GitHubApiError(#[from] octocrab::Error),
+    GitHubApiError(Box<octocrab::Error>),
 
     #[error("Unexpected response format")]
     UnexpectedResponseFormat,
+
+    #[error("Failed to fetch public contributions page: HTTP {0}")]
+    PublicPageError(u16),
+}
+
+/// One entry of the `errors` array of a GraphQL response.
+#[derive(Debug, Clone)]
+pub struct GraphqlError {
+    /// GitHub's error `type`, such as `NOT_FOUND` or `RATE_LIMITED`.
+    pub kind: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for GraphqlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+fn format_graphql_errors(errors: &[GraphqlError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn token_name(from_gh: bool) -> &'static str {
+    if from_gh {
+        "The gh CLI's token"
+    } else {
+        "Token"
+    }
+}
+
+fn format_reset(reset_at: &Option<DateTime<Utc>>) -> String {
+    match reset_at {
+        Some(reset_at) => format!(
+            ", resets at {}",
+            timezone::zone().localize(reset_at).format("%H:%M:%S")
+        ),
+        None => String::new(),
+    }
+}
+
+impl FetchError {
+    /// Exit codes follow BSD `sysexits.h` where one fits.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            FetchError::InvalidInput(_) => 64,          // EX_USAGE
+            FetchError::GraphQl(_) => 65,               // EX_DATAERR
+            FetchError::UserNotFound(_) => 67,          // EX_NOUSER
+            FetchError::Network(_) => 69,               // EX_UNAVAILABLE
+            FetchError::UnexpectedResponseFormat => 70, // EX_SOFTWARE
+            FetchError::RateLimited { .. } => 75,       // EX_TEMPFAIL
+            FetchError::PublicPageError(_) | FetchError::GitHubApiError(_) => 76, // EX_PROTOCOL
+            FetchError::BadCredentials
+            | FetchError::MissingToken(_)
+            | FetchError::MissingScope { .. } => 77, // EX_NOPERM
+        }
+    }
+
+    /// Marks a missing scope as one of the token gitfetch borrowed from the gh
+    /// CLI, which is fixed through gh rather than with a new token.
+    pub(crate) fn blame_gh_token(self) -> Self {
+        match self {
+            FetchError::MissingScope { granted, .. } => FetchError::MissingScope {
+                granted,
+                from_gh: true,
+            },
+            error => error,
+        }
+    }
+
+    /// A suggested fix to print below the error.
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            FetchError::InvalidInput(_) => {
+                Some("Check the command line arguments; see 'gitfetch --help'".to_string())
+            }
+            FetchError::UserNotFound(_) => {
+                Some("Check the spelling of the username passed with -u, or your git user.name".to_string())
+            }
+            FetchError::BadCredentials => Some(
+                "The token may be expired or revoked. Create a new one at https://github.com/settings/tokens and run 'gitfetch token add <TOKEN>'".to_string(),
+            ),
+            FetchError::RateLimited { .. } => Some(
+                "Wait for the rate limit to reset, or use a token: unauthenticated requests have a much lower limit".to_string(),
+            ),
+            FetchError::Network(_) => {
+                Some("Check your internet connection and proxy settings, then try again".to_string())
+            }
+            FetchError::MissingToken(_) => Some(
+                "Run 'gitfetch token add <TOKEN>', set GITHUB_TOKEN, or configure token_command".to_string(),
+            ),
+            FetchError::MissingScope { from_gh: true, .. } => Some(
+                "Run 'gh auth refresh -s read:user' to add the scope, or give gitfetch its own token with 'gitfetch token add <TOKEN>'".to_string(),
+            ),
+            FetchError::MissingScope { from_gh: false, .. } => Some(
+                "Create a token with the read:user scope at https://github.com/settings/tokens and run 'gitfetch token add <TOKEN>'".to_string(),
+            ),
+            FetchError::GraphQl(_) | FetchError::GitHubApiError(_) => {
+                Some("Run 'gitfetch token status' to check that the token is valid".to_string())
+            }
+            FetchError::UnexpectedResponseFormat | FetchError::PublicPageError(_) => Some(
+                "GitHub may have changed its response format; please report this at https://github.com/FabricSoul/gitfetch/issues".to_string(),
+            ),
+        }
+    }
+}
+
+impl From<octocrab::Error> for FetchError {
+    fn from(error: octocrab::Error) -> Self {
+        match error {
+            octocrab::Error::Hyper { source, .. } => FetchError::Network(source.to_string()),
+            octocrab::Error::Service { source, .. } => FetchError::Network(source.to_string()),
+            error => FetchError::GitHubApiError(Box::new(error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_of_failure_exit_with_distinct_codes() {
+        let codes = [
+            FetchError::InvalidInput(String::new()),
+            FetchError::GraphQl(Vec::new()),
+            FetchError::UserNotFound(String::new()),
+            FetchError::Network(String::new()),
+            FetchError::UnexpectedResponseFormat,
+            FetchError::RateLimited { reset_at: None },
+            FetchError::PublicPageError(500),
+            FetchError::BadCredentials,
+        ]
+        .map(|error| error.exit_code());
+        assert_eq!(codes, [64, 65, 67, 69, 70, 75, 76, 77]);
+
+        // Errors the user fixes the same way share a code
+        assert_eq!(FetchError::MissingToken(String::new()).exit_code(), 77);
+        assert_eq!(
+            FetchError::MissingScope {
+                granted: Vec::new(),
+                from_gh: false
+            }
+            .exit_code(),
+            77
+        );
+    }
+
+    #[test]
+    fn missing_scopes_of_the_gh_token_point_to_gh() {
+        let error = FetchError::MissingScope {
+            granted: vec!["repo".to_string(), "gist".to_string()],
+            from_gh: false,
+        };
+        assert_eq!(
+            error.to_string(),
+            "Token lacks the read:user scope (granted: repo, gist)"
+        );
+        assert!(!error.hint().unwrap().contains("gh auth"));
+
+        let error = error.blame_gh_token();
+        assert_eq!(
+            error.to_string(),
+            "The gh CLI's token lacks the read:user scope (granted: repo, gist)"
+        );
+        assert!(error
+            .hint()
+            .unwrap()
+            .contains("gh auth refresh -s read:user"));
+        assert!(matches!(
+            FetchError::BadCredentials.blame_gh_token(),
+            FetchError::BadCredentials
+        ));
+    }
 }
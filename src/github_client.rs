@@ -1,7 +1,14 @@
-use crate::contribution_analyzer::ContributionData;
-use crate::errors::FetchError;
-use chrono::NaiveDate;
-use octocrab::Octocrab;
+use crate::contribution_analyzer::{summarize_contributions, ContributionData};
+use crate::day::{ActivityItem, Commit, DayActivity};
+use crate::errors::{FetchError, GraphqlError};
+use crate::profile::Profile;
+use crate::rate_limit::{send_with_retry, RateLimit};
+use crate::timezone;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+use octocrab::service::middleware::retry::RetryConfig;
+use octocrab::{
+    DefaultOctocrabBuilderConfig, NoAuth, NoSvc, NotLayerReady, Octocrab, OctocrabBuilder,
+};
 use serde_json::Value;
 
 pub async fn fetch_contributions(
@@ -11,12 +18,12 @@ pub async fn fetch_contributions(
     octocrab: Octocrab,
 ) -> Result<ContributionData, FetchError> {
     let query: &str;
-    let response: serde_json::Value;
+    let payload: Value;
 
     if year_specified {
-        let year_int: i32 = year.parse()?;
-        let from = format!("{}-01-01T00:00:00Z", year_int);
-        let to = format!("{}-12-31T23:59:59Z", year_int);
+        let (first_day, last_day) = year_bounds(parse_year(year)?)?;
+        let (from, to) = day_range(first_day, last_day);
+        let (from, to) = (from.to_rfc3339(), to.to_rfc3339());
 
         query = r#"
         query($userName:String!, $from:DateTime!, $to:DateTime!) {
@@ -33,19 +40,20 @@ pub async fn fetch_contributions(
                     }
                 }
             }
+            rateLimit {
+                cost
+            }
         }
     "#;
 
-        response = octocrab
-            .graphql(&serde_json::json!({
-                    "query": query,
-                    "variables": {
-                    "userName": username,
-                    "from": from,
-                    "to": to
-                }
-            }))
-            .await?;
+        payload = serde_json::json!({
+            "query": query,
+            "variables": {
+                "userName": username,
+                "from": from,
+                "to": to
+            }
+        });
     } else {
         query = r#"
         query($userName:String!) {
@@ -62,20 +70,214 @@ pub async fn fetch_contributions(
                     }
                 }
             }
+            rateLimit {
+                cost
+            }
         }
     "#;
 
-        response = octocrab
-            .graphql(&serde_json::json!({
-                "query": query,
-                "variables": {
-                    "userName": username
-                }
-            }))
-            .await?;
+        payload = serde_json::json!({
+            "query": query,
+            "variables": {
+                "userName": username
+            }
+        });
+    }
+
+    let response = graphql(&octocrab, &payload)
+        .await
+        .map_err(|e| user_not_found(e, username))?;
+    ensure_read_user_scope(response.scopes.as_deref())?;
+    parse_contribution_data(&response.data)
+}
+
+pub struct GraphqlResponse {
+    pub data: Value,
+    /// Scopes of a classic token from the `X-OAuth-Scopes` header; fine-grained
+    /// tokens don't report any.
+    pub scopes: Option<Vec<String>>,
+}
+
+#[non_exhaustive]
+pub struct TokenStatus {
+    pub login: String,
+    pub scopes: Option<Vec<String>>,
+    pub rate_limit: u64,
+    pub rate_remaining: u64,
+    pub rate_reset_at: String,
+}
+
+/// An Octocrab builder with octocrab's own retries turned off. They would
+/// retry 5xx and 429 responses at once, underneath `send_with_retry`, and
+/// multiply its attempts while ignoring its backoff.
+pub fn client_builder(
+) -> OctocrabBuilder<NoSvc, DefaultOctocrabBuilderConfig, NoAuth, NotLayerReady> {
+    let mut builder = Octocrab::builder();
+    builder.add_retry_config(RetryConfig::None);
+    builder
+}
+
+/// Builds a client for the given API base, or api.github.com when `None`.
+pub fn build_client(api_url: Option<&str>, token: String) -> Result<Octocrab, FetchError> {
+    let mut builder = client_builder().personal_token(token);
+    if let Some(api_url) = api_url {
+        builder = builder.base_uri(api_url)?;
     }
+    Ok(builder.build()?)
+}
+
+pub fn parse_year(year: &str) -> Result<i32, FetchError> {
+    year.parse()
+        .map_err(|_| FetchError::InvalidInput(format!("'{}' is not a valid year", year)))
+}
+
+/// January 1st and December 31st of `year`.
+pub fn year_bounds(year: i32) -> Result<(NaiveDate, NaiveDate), FetchError> {
+    NaiveDate::from_ymd_opt(year, 1, 1)
+        .zip(NaiveDate::from_ymd_opt(year, 12, 31))
+        .ok_or_else(|| FetchError::InvalidInput(format!("'{}' is not a valid year", year)))
+}
 
-    parse_contribution_data(&response)
+/// The first and last second of the days from `first` to `last`, in the
+/// configured time zone.
+pub fn day_range(
+    first: NaiveDate,
+    last: NaiveDate,
+) -> (DateTime<FixedOffset>, DateTime<FixedOffset>) {
+    let zone = timezone::zone();
+    let end = last
+        .succ_opt()
+        .map(|next| zone.start_of_day(next) - Duration::seconds(1))
+        .unwrap_or_else(|| zone.start_of_day(last));
+    (zone.start_of_day(first), end)
+}
+
+/// Posts a GraphQL query, keeping the response headers that octocrab's own
+/// `graphql` helper throws away, and turning HTTP failures and the `errors`
+/// array of the response into typed errors.
+pub async fn graphql(octocrab: &Octocrab, payload: &Value) -> Result<GraphqlResponse, FetchError> {
+    let response = send_with_retry(|| octocrab._post("/graphql", Some(payload))).await?;
+    let rate_limit = RateLimit::from_headers(response.headers());
+    let reset_at = rate_limit.as_ref().and_then(|limit| limit.reset_at);
+
+    match response.status().as_u16() {
+        401 => return Err(FetchError::BadCredentials),
+        403 | 429 if rate_limit.as_ref().is_some_and(RateLimit::is_exhausted) => {
+            return Err(FetchError::RateLimited { reset_at })
+        }
+        429 => return Err(FetchError::RateLimited { reset_at }),
+        _ => {}
+    }
+
+    let scopes = response
+        .headers()
+        .get("x-oauth-scopes")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|scope| !scope.is_empty())
+                .map(str::to_string)
+                .collect()
+        });
+    let response = octocrab::map_github_error(response).await?;
+    let body = octocrab.body_to_string(response).await?;
+    let data: Value =
+        serde_json::from_str(&body).map_err(|_| FetchError::UnexpectedResponseFormat)?;
+    if let Some(rate_limit) = &rate_limit {
+        rate_limit.report(data["data"]["rateLimit"]["cost"].as_u64());
+    }
+
+    if let Some(error) = graphql_error(&data, reset_at) {
+        return Err(error);
+    }
+
+    Ok(GraphqlResponse { data, scopes })
+}
+
+/// Turns the `errors` array of a GraphQL response into an error, if there is
+/// one. A `RATE_LIMITED` entry wins over the others.
+fn graphql_error(data: &Value, reset_at: Option<DateTime<Utc>>) -> Option<FetchError> {
+    let errors: Vec<GraphqlError> = data["errors"]
+        .as_array()?
+        .iter()
+        .map(|error| GraphqlError {
+            kind: error["type"].as_str().map(str::to_string),
+            message: error["message"]
+                .as_str()
+                .unwrap_or("unknown error")
+                .to_string(),
+        })
+        .collect();
+    if errors.is_empty() {
+        return None;
+    }
+    if errors
+        .iter()
+        .any(|error| error.kind.as_deref() == Some("RATE_LIMITED"))
+    {
+        return Some(FetchError::RateLimited { reset_at });
+    }
+    Some(FetchError::GraphQl(errors))
+}
+
+/// A `NOT_FOUND` error on a user query means the login doesn't exist.
+fn user_not_found(error: FetchError, username: &str) -> FetchError {
+    match error {
+        FetchError::GraphQl(errors)
+            if errors
+                .iter()
+                .any(|error| error.kind.as_deref() == Some("NOT_FOUND")) =>
+        {
+            FetchError::UserNotFound(username.to_string())
+        }
+        error => error,
+    }
+}
+
+/// Classic tokens need `read:user` (or the broader `user`) to see private
+/// contributions.
+pub fn has_read_user_scope(scopes: &[String]) -> bool {
+    scopes
+        .iter()
+        .any(|scope| scope == "read:user" || scope == "user")
+}
+
+/// Fine-grained tokens report no scopes and are let through.
+fn ensure_read_user_scope(scopes: Option<&[String]>) -> Result<(), FetchError> {
+    match scopes {
+        Some(scopes) if !has_read_user_scope(scopes) => Err(FetchError::MissingScope {
+            granted: scopes.to_vec(),
+            from_gh: false,
+        }),
+        _ => Ok(()),
+    }
+}
+
+pub async fn fetch_token_status(octocrab: &Octocrab) -> Result<TokenStatus, FetchError> {
+    let response = graphql(
+        octocrab,
+        &serde_json::json!({
+            "query": "query { viewer { login } rateLimit { limit remaining resetAt } }"
+        }),
+    )
+    .await?;
+    let data = &response.data["data"];
+
+    Ok(TokenStatus {
+        login: data["viewer"]["login"]
+            .as_str()
+            .ok_or(FetchError::UnexpectedResponseFormat)?
+            .to_string(),
+        scopes: response.scopes,
+        rate_limit: data["rateLimit"]["limit"].as_u64().unwrap_or(0),
+        rate_remaining: data["rateLimit"]["remaining"].as_u64().unwrap_or(0),
+        rate_reset_at: data["rateLimit"]["resetAt"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+    })
 }
 
 fn parse_contribution_data(value: &Value) -> Result<ContributionData, FetchError> {
@@ -86,9 +288,6 @@ fn parse_contribution_data(value: &Value) -> Result<ContributionData, FetchError
         .ok_or(FetchError::UnexpectedResponseFormat)? as u32;
 
     let mut daily_contributions = Vec::new();
-    let mut longest_streak = 0;
-    let mut max_contributions = 0;
-    let mut streak = 0;
 
     if let Some(weeks) = calendar["weeks"].as_array() {
         for week in weeks {
@@ -104,34 +303,442 @@ fn parse_contribution_data(value: &Value) -> Result<ContributionData, FetchError
                             .ok_or(FetchError::UnexpectedResponseFormat)?,
                         "%Y-%m-%d",
                     )
-                    .map_err(|_| FetchError::DateCreationError)?;
+                    .map_err(|_| FetchError::UnexpectedResponseFormat)?;
 
                     daily_contributions.push((date, count));
+                }
+            }
+        }
+    }
+
+    Ok(summarize_contributions(total, daily_contributions))
+}
+
+/// Lists the logins of an organization team's members. Requires a token with
+/// the `read:org` scope.
+pub async fn fetch_team_members(
+    octocrab: &Octocrab,
+    org: &str,
+    team: &str,
+) -> Result<Vec<String>, FetchError> {
+    let query = r#"
+        query($org:String!, $team:String!, $cursor:String) {
+            organization(login: $org) {
+                team(slug: $team) {
+                    members(first: 100, after: $cursor) {
+                        pageInfo {
+                            hasNextPage
+                            endCursor
+                        }
+                        nodes {
+                            login
+                        }
+                    }
+                }
+            }
+        }
+    "#;
+
+    let mut members = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let response = graphql(
+            octocrab,
+            &serde_json::json!({
+                "query": query,
+                "variables": {
+                    "org": org,
+                    "team": team,
+                    "cursor": cursor
+                }
+            }),
+        )
+        .await?;
+
+        let connection = &response.data["data"]["organization"]["team"]["members"];
+        if connection.is_null() {
+            return Err(FetchError::InvalidInput(format!(
+                "team '{}/{}' not found or not visible to this token",
+                org, team
+            )));
+        }
+        if let Some(nodes) = connection["nodes"].as_array() {
+            members.extend(
+                nodes
+                    .iter()
+                    .filter_map(|node| node["login"].as_str())
+                    .map(str::to_string),
+            );
+        }
 
-                    if count > 0 {
-                        streak += 1;
-                        longest_streak = longest_streak.max(streak);
-                        max_contributions = max_contributions.max(count);
-                    } else {
-                        streak = 0;
+        if !connection["pageInfo"]["hasNextPage"]
+            .as_bool()
+            .unwrap_or(false)
+        {
+            break;
+        }
+        cursor = connection["pageInfo"]["endCursor"]
+            .as_str()
+            .map(str::to_string);
+    }
+
+    Ok(members)
+}
+
+/// Author timestamps of `username`'s commits between `from` and `to`, from the
+/// commit search API. Search only covers default branches and stops at 1000
+/// results.
+pub async fn fetch_commit_times(
+    octocrab: &Octocrab,
+    username: &str,
+    from: DateTime<FixedOffset>,
+    to: DateTime<FixedOffset>,
+) -> Result<Vec<DateTime<FixedOffset>>, FetchError> {
+    search_commits(octocrab, username, from, to)
+        .await?
+        .iter()
+        .map(|item| commit_time(item).ok_or(FetchError::UnexpectedResponseFormat))
+        .collect()
+}
+
+/// The commit search results for `username` between `from` and `to`, oldest
+/// first.
+/// Percent-encodes `value` for a query string. Offsets carry a '+' that
+/// would otherwise read as a space, and usernames come from the command line.
+fn query_escape(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+async fn search_commits(
+    octocrab: &Octocrab,
+    username: &str,
+    from: DateTime<FixedOffset>,
+    to: DateTime<FixedOffset>,
+) -> Result<Vec<Value>, FetchError> {
+    let author = query_escape(username);
+    let from = query_escape(&from.to_rfc3339());
+    let to = query_escape(&to.to_rfc3339());
+    const PER_PAGE: usize = 100;
+    const MAX_PAGES: usize = 10;
+
+    let mut commits = Vec::new();
+    for page in 1..=MAX_PAGES {
+        let url = format!(
+            "/search/commits?q=author:{}+author-date:{}..{}&sort=author-date&order=asc&per_page={}&page={}",
+            author, from, to, PER_PAGE, page
+        );
+        let response = send_with_retry(|| octocrab._get(url.as_str())).await?;
+        let rate_limit = RateLimit::from_headers(response.headers());
+        let reset_at = rate_limit.as_ref().and_then(|limit| limit.reset_at);
+        match response.status().as_u16() {
+            401 => return Err(FetchError::BadCredentials),
+            403 | 429 => return Err(FetchError::RateLimited { reset_at }),
+            // Search answers with a validation error for unknown users
+            422 => return Err(FetchError::UserNotFound(username.to_string())),
+            _ => {}
+        }
+        let response = octocrab::map_github_error(response).await?;
+        let body = octocrab.body_to_string(response).await?;
+        if let Some(rate_limit) = &rate_limit {
+            rate_limit.report(None);
+        }
+        let mut data: Value =
+            serde_json::from_str(&body).map_err(|_| FetchError::UnexpectedResponseFormat)?;
+
+        let items = data["items"]
+            .as_array_mut()
+            .ok_or(FetchError::UnexpectedResponseFormat)?;
+        let full = items.len() == PER_PAGE;
+        commits.append(items);
+        if !full {
+            break;
+        }
+    }
+
+    Ok(commits)
+}
+
+fn commit_time(item: &Value) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(item["commit"]["author"]["date"].as_str()?).ok()
+}
+
+/// A commit search result as listed for a day: the repository, the short
+/// hash and the first line of the message.
+fn day_commit(item: &Value) -> Option<Commit> {
+    Some(Commit {
+        repository: item["repository"]["full_name"].as_str()?.to_string(),
+        time: commit_time(item)?,
+        hash: item["sha"].as_str()?.chars().take(7).collect(),
+        subject: item["commit"]["message"]
+            .as_str()?
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+/// The profile details shown in the info panel. Stars are summed over all of
+/// the user's own repositories, a page of 100 at a time.
+pub async fn fetch_profile(octocrab: &Octocrab, username: &str) -> Result<Profile, FetchError> {
+    let query = r#"
+        query($userName:String!, $cursor:String) {
+            user(login: $userName) {
+                name
+                bio
+                company
+                location
+                createdAt
+                followers { totalCount }
+                following { totalCount }
+                publicRepositories: repositories(privacy: PUBLIC, ownerAffiliations: OWNER) {
+                    totalCount
+                }
+                starredRepositories: repositories(
+                    first: 100,
+                    after: $cursor,
+                    privacy: PUBLIC,
+                    ownerAffiliations: OWNER,
+                    isFork: false,
+                    orderBy: {field: STARGAZERS, direction: DESC}
+                ) {
+                    pageInfo {
+                        hasNextPage
+                        endCursor
                     }
+                    nodes { stargazerCount }
                 }
             }
+            rateLimit {
+                cost
+            }
+        }
+    "#;
+
+    let mut user = Value::Null;
+    let mut stars = 0;
+    let mut cursor: Option<String> = None;
+    loop {
+        let response = graphql(
+            octocrab,
+            &serde_json::json!({
+                "query": query,
+                "variables": {
+                    "userName": username,
+                    "cursor": cursor
+                }
+            }),
+        )
+        .await
+        .map_err(|e| user_not_found(e, username))?;
+
+        let page = &response.data["data"]["user"];
+        if page.is_null() {
+            return Err(FetchError::UserNotFound(username.to_string()));
         }
+        let connection = &page["starredRepositories"];
+        let counts: Vec<u64> = connection["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|node| node["stargazerCount"].as_u64())
+            .collect();
+        stars += counts.iter().sum::<u64>();
+        if user.is_null() {
+            user = page.clone();
+        }
+
+        // Repositories come most starred first, so the rest have none either
+        if counts.last().is_none_or(|&count| count == 0)
+            || !connection["pageInfo"]["hasNextPage"]
+                .as_bool()
+                .unwrap_or(false)
+        {
+            break;
+        }
+        cursor = connection["pageInfo"]["endCursor"]
+            .as_str()
+            .map(str::to_string);
     }
 
-    // Calculate current streak
-    let current_streak = daily_contributions
+    let created_at = user["createdAt"]
+        .as_str()
+        .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+        .ok_or(FetchError::UnexpectedResponseFormat)?;
+    // Empty strings mean the field was cleared; bios may span lines
+    let text = |field: &str| {
+        user[field]
+            .as_str()
+            .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|text| !text.is_empty())
+    };
+    let count = |connection: &Value| connection["totalCount"].as_u64().unwrap_or(0);
+
+    Ok(Profile {
+        name: text("name"),
+        bio: text("bio"),
+        company: text("company"),
+        location: text("location"),
+        followers: count(&user["followers"]),
+        following: count(&user["following"]),
+        public_repositories: count(&user["publicRepositories"]),
+        stars,
+        created_at: timezone::zone().localize(&created_at).date(),
+    })
+}
+
+/// The commits, pull requests, issues and reviews `username` contributed on
+/// `date` in the configured time zone. Commits come from the commit search, so
+/// only those on default branches are listed. Other lists are capped at 100
+/// entries per kind, far more than a day usually holds.
+pub async fn fetch_day_activity(
+    octocrab: &Octocrab,
+    username: &str,
+    date: NaiveDate,
+) -> Result<DayActivity, FetchError> {
+    let query = r#"
+        query($userName:String!, $from:DateTime!, $to:DateTime!) {
+            user(login: $userName) {
+                contributionsCollection(from: $from, to: $to) {
+                    pullRequestContributions(first: 100) {
+                        nodes { pullRequest { number title repository { nameWithOwner } } }
+                    }
+                    issueContributions(first: 100) {
+                        nodes { issue { number title repository { nameWithOwner } } }
+                    }
+                    pullRequestReviewContributions(first: 100) {
+                        nodes { pullRequest { number title repository { nameWithOwner } } }
+                    }
+                }
+            }
+            rateLimit {
+                cost
+            }
+        }
+    "#;
+    let (from, to) = day_range(date, date);
+
+    let response = graphql(
+        octocrab,
+        &serde_json::json!({
+            "query": query,
+            "variables": {
+                "userName": username,
+                "from": from.to_rfc3339(),
+                "to": to.to_rfc3339()
+            }
+        }),
+    )
+    .await
+    .map_err(|e| user_not_found(e, username))?;
+    ensure_read_user_scope(response.scopes.as_deref())?;
+
+    let collection = &response.data["data"]["user"]["contributionsCollection"];
+    if collection.is_null() {
+        return Err(FetchError::UnexpectedResponseFormat);
+    }
+    let commits = search_commits(octocrab, username, from, to)
+        .await?
         .iter()
-        .rev()
-        .take_while(|(_, count)| *count > 0)
-        .count() as u32;
-
-    Ok(ContributionData {
-        total,
-        longest_streak,
-        current_streak,
-        max_contributions,
-        daily_contributions,
+        .filter_map(day_commit)
+        .collect();
+
+    Ok(DayActivity {
+        commits,
+        pull_requests: activity_items(&collection["pullRequestContributions"], "pullRequest"),
+        issues: activity_items(&collection["issueContributions"], "issue"),
+        reviews: activity_items(&collection["pullRequestReviewContributions"], "pullRequest"),
+        local_commits: Vec::new(),
     })
 }
+
+fn activity_items(connection: &Value, field: &str) -> Vec<ActivityItem> {
+    connection["nodes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|node| {
+            let item = &node[field];
+            Some(ActivityItem {
+                repository: item["repository"]["nameWithOwner"].as_str()?.to_string(),
+                number: item["number"].as_u64()?,
+                title: item["title"].as_str()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graphql_errors_are_typed() {
+        let not_found = serde_json::json!({
+            "data": { "user": null },
+            "errors": [{ "type": "NOT_FOUND", "message": "Could not resolve to a User" }]
+        });
+        let error = graphql_error(&not_found, None).unwrap();
+        assert!(matches!(&error, FetchError::GraphQl(errors) if errors.len() == 1));
+        assert!(matches!(
+            user_not_found(error, "ghost"),
+            FetchError::UserNotFound(name) if name == "ghost"
+        ));
+
+        let reset_at = DateTime::from_timestamp(1_700_000_000, 0);
+        let rate_limited = serde_json::json!({
+            "errors": [
+                { "type": "MAX_NODE_LIMIT_EXCEEDED", "message": "too many nodes" },
+                { "type": "RATE_LIMITED", "message": "API rate limit exceeded" }
+            ]
+        });
+        assert!(matches!(
+            graphql_error(&rate_limited, reset_at),
+            Some(FetchError::RateLimited { reset_at: at }) if at == reset_at
+        ));
+        // Other errors aren't mistaken for a missing user
+        assert!(matches!(
+            user_not_found(graphql_error(&rate_limited, None).unwrap(), "ghost"),
+            FetchError::RateLimited { .. }
+        ));
+
+        assert!(graphql_error(&serde_json::json!({ "data": {} }), None).is_none());
+        assert!(graphql_error(&serde_json::json!({ "errors": [] }), None).is_none());
+    }
+
+    #[test]
+    fn query_values_are_percent_encoded() {
+        assert_eq!(query_escape("octo-cat_1.x"), "octo-cat_1.x");
+        assert_eq!(query_escape("Jane Doe&x"), "Jane%20Doe%26x");
+        assert_eq!(
+            query_escape("2024-03-14T00:00:00+02:00"),
+            "2024-03-14T00%3A00%3A00%2B02%3A00"
+        );
+    }
+
+    #[test]
+    fn day_commits_show_the_first_line_of_the_message() {
+        let item = serde_json::json!({
+            "sha": "0123456789abcdef",
+            "repository": { "full_name": "octocat/hello-world" },
+            "commit": {
+                "author": { "date": "2024-03-05T09:14:00+01:00" },
+                "message": "Fix the greeting\n\nIt said hallo."
+            }
+        });
+        let commit = day_commit(&item).unwrap();
+        assert_eq!(commit.repository, "octocat/hello-world");
+        assert_eq!(commit.hash, "0123456");
+        assert_eq!(commit.subject, "Fix the greeting");
+        assert_eq!(commit.time.to_rfc3339(), "2024-03-05T09:14:00+01:00");
+        assert!(day_commit(&serde_json::json!({ "sha": "0123456" })).is_none());
+    }
+}
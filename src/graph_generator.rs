@@ -1,88 +1,454 @@
-use crate::contribution_analyzer::calculate_contribution_ranges;
-use chrono::{Datelike, Duration, Local, NaiveDate};
+use crate::contribution_analyzer::{calculate_contribution_ranges, calculate_count_ranges};
+use crate::info::{InfoLine, InfoStyle};
+use crate::punchcard::Punchcard;
+use crate::timezone;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
 use colored::Colorize;
 use colored::CustomColor;
+use std::collections::HashMap;
 
-use crate::config_manager::{Config, GraphColors, TextColors};
+use crate::config_manager::{Config, GraphColors, InfoConfig, Orientation, TextColors};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
 
 pub fn generate_contribution_graph(
     daily_contributions: &[(NaiveDate, u32)],
     year_specified: bool,
     config: &Config,
 ) -> String {
-    let mut graph = String::new();
-    let current_date = Local::now().naive_local().date();
-    let months = [
-        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
-    ];
-
-    // Calculate the date range
-    let start_date = daily_contributions
-        .first()
-        .map(|(date, _)| *date)
-        .unwrap_or(current_date);
-    let end_date = daily_contributions
-        .last()
-        .map(|(date, _)| *date)
-        .unwrap_or(current_date);
-    let total_days = (end_date - start_date).num_days() as usize;
-    let num_weeks = (total_days / 7) + 1;
-
-    let percentiles = config
-        .graph_data
-        .as_ref()
-        .map(|data| data.percentiles)
-        .unwrap_or([0, 30, 60, 90]);
+    let (start_date, end_date) = date_range(daily_contributions);
+
+    let percentiles = percentiles(config);
 
     // Calculate contribution ranges
     let contribution_ranges = calculate_contribution_ranges(daily_contributions, &percentiles);
+
+    let counts: HashMap<NaiveDate, u32> = daily_contributions.iter().copied().collect();
+    let mut graph = render_grid(
+        start_date,
+        end_date,
+        year_specified,
+        config,
+        |date| match counts.get(&date) {
+            Some(&count) => level_symbol(count, &contribution_ranges).to_string(),
+            None => "  ".to_string(),
+        },
+    );
+    graph += "\nLess   ░░  ▒▒  ▓▓  ██ More\n";
+    graph
+}
+
+/// Like `generate_contribution_graph`, but already colored and with the days
+/// picked by `highlight` drawn in reverse video, e.g. the cursor of the
+/// interactive view. Without `colors` they are marked with `>` instead.
+pub fn generate_highlighted_graph(
+    daily_contributions: &[(NaiveDate, u32)],
+    year_specified: bool,
+    highlight: impl Fn(NaiveDate) -> bool,
+    config: &Config,
+    colors: bool,
+) -> String {
+    let (start_date, end_date) = date_range(daily_contributions);
+
+    let percentiles = percentiles(config);
+    let contribution_ranges = calculate_contribution_ranges(daily_contributions, &percentiles);
+
+    let counts: HashMap<NaiveDate, u32> = daily_contributions.iter().copied().collect();
+    render_grid(start_date, end_date, year_specified, config, |date| {
+        let symbol = match counts.get(&date) {
+            Some(&count) => level_symbol(count, &contribution_ranges),
+            None => "  ",
+        };
+        if !highlight(date) {
+            colorize_graph_line(symbol, config, colors)
+        } else if colors {
+            colorize_graph_line(symbol, config, colors)
+                .reversed()
+                .to_string()
+        } else {
+            // Reversing needs colors; mark the cell instead, keeping its level
+            format!(">{}", symbol.chars().last().unwrap_or(' '))
+        }
+    })
+}
+
+/// Renders one day per cell, where `compare` contributed more than `base`
+/// shows in the compare color and the reverse in the base color, with the
+/// glyph giving the size of the difference. Days are laid out by `base`'s
+/// dates. The result is already colored, so print it as is rather than
+/// through `render_colored_graph`. Without `colors` the compare side uses
+/// ASCII glyphs so the two can still be told apart.
+pub fn generate_diverging_graph(
+    base: &[(NaiveDate, u32)],
+    compare: &[(NaiveDate, u32)],
+    labels: (&str, &str),
+    year_specified: bool,
+    config: &Config,
+    colors: bool,
+) -> String {
+    let (start_date, end_date) = date_range(base);
+
+    let compare: HashMap<NaiveDate, u32> = compare.iter().copied().collect();
+    let pairs: HashMap<NaiveDate, (u32, u32)> = base
+        .iter()
+        .map(|&(date, a)| (date, (a, compare.get(&date).copied().unwrap_or(0))))
+        .collect();
+    let differences: Vec<(NaiveDate, u32)> = pairs
+        .iter()
+        .map(|(&date, &(a, b))| (date, a.abs_diff(b)))
+        .collect();
+    let percentiles = percentiles(config);
+    let ranges = calculate_contribution_ranges(&differences, &percentiles);
+
+    let (base_color, compare_color) = compare_colors(config);
+    let paint = |text: &str, color| {
+        if colors {
+            text.custom_color(color).to_string()
+        } else {
+            text.to_string()
+        }
+    };
+    let tie_symbol = "··";
+    // Without colors the second user gets glyphs of their own
+    let compare_symbol = |count| {
+        if colors {
+            level_symbol(count, &ranges)
+        } else {
+            ascii_level_symbol(count, &ranges)
+        }
+    };
+
+    let mut graph = render_grid(
+        start_date,
+        end_date,
+        year_specified,
+        config,
+        |date| match pairs.get(&date) {
+            Some(&(a, b)) if a > b => paint(level_symbol(a - b, &ranges), base_color),
+            Some(&(a, b)) if b > a => paint(compare_symbol(b - a), compare_color),
+            Some(&(a, _)) if a > 0 => tie_symbol.to_string(),
+            _ => "  ".to_string(),
+        },
+    );
+    graph += &format!(
+        "\n{} {}  {} {}  tie {}\n",
+        labels.0,
+        paint("██", base_color),
+        labels.1,
+        paint(if colors { "██" } else { "##" }, compare_color),
+        tie_symbol
+    );
+    graph
+}
+
+/// Renders commits by weekday and hour with the calendar's glyphs, ready for
+/// `render_colored_graph`.
+pub fn generate_punchcard(punchcard: &Punchcard, config: &Config) -> String {
+    const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+    let percentiles = percentiles(config);
+    let ranges = calculate_count_ranges(punchcard.iter().flatten().copied(), &percentiles);
+
+    let mut graph = "    ".to_string();
+    for hour in (0..24).step_by(3) {
+        graph += &format!("{:<6}", hour);
+    }
+    graph += "\n";
+    let first_row = week_start(config).num_days_from_sunday() as usize;
+    for day in (0..7).map(|row| (first_row + row) % 7) {
+        graph += &format!("{} ", WEEKDAYS[day]);
+        for &count in &punchcard[day] {
+            graph += level_symbol(count, &ranges);
+        }
+        graph += "\n";
+    }
+    graph += "\nLess   ░░  ▒▒  ▓▓  ██ More\n";
+    graph
+}
+
+fn level_symbol(count: u32, contribution_ranges: &[u32]) -> &'static str {
+    match count {
+        0 => "  ",
+        c if c <= contribution_ranges[1] => "░░",
+        c if c <= contribution_ranges[2] => "▒▒",
+        c if c <= contribution_ranges[3] => "▓▓",
+        _ => "██",
+    }
+}
+
+/// Like `level_symbol`, in ASCII for telling a second set of levels apart
+/// without colors.
+fn ascii_level_symbol(count: u32, contribution_ranges: &[u32]) -> &'static str {
+    match count {
+        0 => "  ",
+        c if c <= contribution_ranges[1] => "..",
+        c if c <= contribution_ranges[2] => "::",
+        c if c <= contribution_ranges[3] => "**",
+        _ => "##",
+    }
+}
+
+/// Lays out one cell per day, a column per week starting on the configured
+/// `week_start`, under a row of month names. Cells are placed by date, so
+/// ranges needn't begin on a week boundary; days outside the range stay blank.
+/// With a vertical `orientation` the grid is transposed instead.
+fn render_grid(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    year_specified: bool,
+    config: &Config,
+    cell: impl Fn(NaiveDate) -> String,
+) -> String {
+    if config.orientation == Some(Orientation::Vertical) {
+        return render_vertical_grid(start_date, end_date, config, cell);
+    }
+
+    let mut graph = String::new();
+    let grid_start = week_start_on_or_before(start_date, week_start(config));
+    let num_weeks = ((end_date - grid_start).num_days() / 7 + 1) as usize;
+
     // Calculate column-based month spans
-    let month_spans = calculate_month_spans(start_date, end_date, num_weeks, year_specified);
+    let month_spans = calculate_month_spans(start_date, grid_start, num_weeks);
 
-    // Add month names with proper spacing
+    // Add month names with proper spacing, leaving out those too narrow to
+    // fit and, for rolling ranges, the partial month at the start
     graph += "    ";
-    for (month, span) in month_spans.iter() {
-        graph += &format!("{:<width$}", months[*month], width = span * 2);
+    for (index, (month, span)) in month_spans.iter().enumerate() {
+        let label = if *span < 2 || (!year_specified && index == 0 && *span < 3) {
+            ""
+        } else {
+            MONTHS[*month]
+        };
+        graph += &format!("{:<width$}", label, width = span * 2);
     }
     graph += "\n";
 
     // Generate the graph
     for day in 0..7 {
-        let weekday = match day {
-            1 => "Mon",
-            3 => "Wed",
-            5 => "Fri",
+        let weekday = match (grid_start + Duration::days(day)).weekday() {
+            Weekday::Mon => "Mon",
+            Weekday::Wed => "Wed",
+            Weekday::Fri => "Fri",
             _ => "   ",
         };
         graph += &format!("{} ", weekday);
-        for week in 0..num_weeks {
-            let index = week * 7 + day;
-            if index < daily_contributions.len() {
-                let (_, count) = daily_contributions[index];
-                let symbol = match count {
-                    0 => "  ",
-                    c if c <= contribution_ranges[1] => "░░",
-                    c if c <= contribution_ranges[2] => "▒▒",
-                    c if c <= contribution_ranges[3] => "▓▓",
-                    _ => "██",
-                };
-                graph += symbol;
+        for week in 0..num_weeks as i64 {
+            let date = grid_start + Duration::days(week * 7 + day);
+            if (start_date..=end_date).contains(&date) {
+                graph += &cell(date);
             } else {
                 graph += "  ";
             }
         }
         graph += "\n";
     }
-    graph += "\nLess   ░░  ▒▒  ▓▓  ██ More\n";
     graph
 }
 
-pub fn print_colored_graph(graph: &str, info: &[String], config: &Config) {
-    let graph_lines: Vec<&str> = graph.lines().collect();
+/// Lays out a row per week under a header of weekday initials, naming each
+/// month in the left margin on the first week that falls in it.
+fn render_vertical_grid(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    config: &Config,
+    cell: impl Fn(NaiveDate) -> String,
+) -> String {
+    let grid_start = week_start_on_or_before(start_date, week_start(config));
+    let num_weeks = (end_date - grid_start).num_days() / 7 + 1;
 
-    // Print the first line
-    println!("{}", graph_lines[0]);
+    let mut graph = "    ".to_string();
+    for day in 0..7 {
+        let weekday = (grid_start + Duration::days(day)).weekday().to_string();
+        graph += &format!("{:<2}", &weekday[..1]);
+    }
+    graph += "\n";
+
+    let mut previous_month = None;
+    for week in 0..num_weeks {
+        let week_start = grid_start + Duration::days(week * 7);
+        let month = week_start.max(start_date).month0() as usize;
+        if previous_month != Some(month) {
+            graph += &format!("{} ", MONTHS[month]);
+            previous_month = Some(month);
+        } else {
+            graph += "    ";
+        }
+        for day in 0..7 {
+            let date = week_start + Duration::days(day);
+            if (start_date..=end_date).contains(&date) {
+                graph += &cell(date);
+            } else {
+                graph += "  ";
+            }
+        }
+        graph += "\n";
+    }
+    graph
+}
 
+/// The percentiles that split counts into the four glyph levels.
+fn percentiles(config: &Config) -> [usize; 4] {
+    config
+        .graph_data
+        .as_ref()
+        .map(|data| data.percentiles)
+        .unwrap_or([0, 30, 60, 90])
+}
+
+/// The first and last day of `daily_contributions`, or today for both when
+/// there are none.
+fn date_range(daily_contributions: &[(NaiveDate, u32)]) -> (NaiveDate, NaiveDate) {
+    let today = timezone::today();
+    let first = daily_contributions.first().map_or(today, |&(date, _)| date);
+    let last = daily_contributions.last().map_or(today, |&(date, _)| date);
+    (first, last)
+}
+
+fn week_start(config: &Config) -> Weekday {
+    config.week_start.unwrap_or_default().weekday()
+}
+
+fn week_start_on_or_before(date: NaiveDate, week_start: Weekday) -> NaiveDate {
+    let offset =
+        (date.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7;
+    date - Duration::days(offset as i64)
+}
+
+fn compare_colors(config: &Config) -> (CustomColor, CustomColor) {
+    let colors = config.compare_colors.as_ref();
+    (
+        colors
+            .and_then(|colors| get_color(&colors.first).ok())
+            .unwrap_or(CustomColor {
+                r: 56,
+                g: 139,
+                b: 253,
+            }),
+        colors
+            .and_then(|colors| get_color(&colors.second).ok())
+            .unwrap_or(CustomColor {
+                r: 219,
+                g: 109,
+                b: 40,
+            }),
+    )
+}
+
+/// The graph with its level glyphs colored, its first line (the month names)
+/// left as is, followed by a blank line and the colored info lines. Without
+/// `colors` it's all plain text.
+pub fn render_colored_graph(
+    graph: &str,
+    info: &[InfoLine],
+    config: &Config,
+    colors: bool,
+) -> String {
+    let mut out = String::new();
+    let mut graph_lines = graph.lines();
+
+    if let Some(first_line) = graph_lines.next() {
+        out += first_line;
+        out += "\n";
+        for graph_line in graph_lines {
+            out += &colorize_graph_line(graph_line, config, colors);
+            out += "\n";
+        }
+        out += "\n";
+    }
+
+    for info_line in colorize_info(info, config, colors) {
+        out += &info_line;
+        out += "\n";
+    }
+    out
+}
+
+/// Colors the level glyphs in a line of a graph with the configured graph
+/// colors, leaving everything else as is.
+pub fn colorize_graph_line(line: &str, config: &Config, colors: bool) -> String {
+    if !colors {
+        return line.to_string();
+    }
+    line.chars()
+        .map(|c| match level_color(c, config) {
+            Some(color) => c.to_string().custom_color(color).to_string(),
+            None => c.to_string(),
+        })
+        .collect()
+}
+
+/// Colors a logo line by line. `$1` to `$4` switch to the colors of the four
+/// graph levels; text before the first of them takes the info color.
+pub fn colorize_logo(art: &str, config: &Config, colors: bool) -> Vec<String> {
+    let info_color = config
+        .text_colors
+        .as_ref()
+        .and_then(|text_colors| get_color(&text_colors.info_color).ok())
+        .unwrap_or(CustomColor {
+            r: 0,
+            g: 255,
+            b: 255,
+        });
+
+    logo_segments(art)
+        .into_iter()
+        .map(|segments| {
+            segments
+                .into_iter()
+                .map(|(level, text)| {
+                    if !colors {
+                        return text;
+                    }
+                    let color = level
+                        .and_then(|glyph| level_color(glyph, config))
+                        .unwrap_or(info_color);
+                    text.custom_color(color).to_string()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Splits each line of a logo into runs of text with the level glyph whose
+/// color they take, or `None` for the info color. A color carries over to the
+/// following lines until the next `$1` to `$4`.
+fn logo_segments(art: &str) -> Vec<Vec<(Option<char>, String)>> {
+    let mut level = None;
+    art.lines()
+        .map(|line| {
+            let mut segments = Vec::new();
+            let mut segment = String::new();
+            let mut chars = line.chars().peekable();
+            while let Some(c) = chars.next() {
+                let glyph = chars.peek().and_then(|next| match next {
+                    '1' => Some('░'),
+                    '2' => Some('▒'),
+                    '3' => Some('▓'),
+                    '4' => Some('█'),
+                    _ => None,
+                });
+                match glyph {
+                    Some(glyph) if c == '$' => {
+                        chars.next();
+                        if !segment.is_empty() {
+                            segments.push((level, std::mem::take(&mut segment)));
+                        }
+                        level = Some(glyph);
+                    }
+                    _ => segment.push(c),
+                }
+            }
+            if !segment.is_empty() {
+                segments.push((level, segment));
+            }
+            segments
+        })
+        .collect()
+}
+
+fn level_color(glyph: char, config: &Config) -> Option<CustomColor> {
     // Define default graph colors
     let default_graph_colors = GraphColors {
         level1: "13,68,41".to_string(),
@@ -97,168 +463,99 @@ pub fn print_colored_graph(graph: &str, info: &[String], config: &Config) {
         .as_ref()
         .unwrap_or(&default_graph_colors);
 
-    // Print graph
-    for (_index, graph_line) in graph_lines.iter().enumerate().skip(1) {
-        for c in graph_line.chars() {
-            let colored_char = match c {
-                '░' => c
-                    .to_string()
-                    .custom_color(get_color(&graph_colors.level1).unwrap_or(CustomColor {
-                        r: 13,
-                        g: 68,
-                        b: 41,
-                    })),
-                '▒' => c
-                    .to_string()
-                    .custom_color(get_color(&graph_colors.level2).unwrap_or(CustomColor {
-                        r: 1,
-                        g: 108,
-                        b: 49,
-                    })),
-                '▓' => c
-                    .to_string()
-                    .custom_color(get_color(&graph_colors.level3).unwrap_or(CustomColor {
-                        r: 38,
-                        g: 166,
-                        b: 65,
-                    })),
-                '█' => c
-                    .to_string()
-                    .custom_color(get_color(&graph_colors.level4).unwrap_or(CustomColor {
-                        r: 57,
-                        g: 211,
-                        b: 83,
-                    })),
-                _ => c.to_string().normal(),
-            };
-            print!("{}", colored_char);
-        }
-        println!();
-    }
-
-    // Print separator
-    println!();
+    let color = match glyph {
+        '░' => get_color(&graph_colors.level1).unwrap_or(CustomColor {
+            r: 13,
+            g: 68,
+            b: 41,
+        }),
+        '▒' => get_color(&graph_colors.level2).unwrap_or(CustomColor {
+            r: 1,
+            g: 108,
+            b: 49,
+        }),
+        '▓' => get_color(&graph_colors.level3).unwrap_or(CustomColor {
+            r: 38,
+            g: 166,
+            b: 65,
+        }),
+        '█' => get_color(&graph_colors.level4).unwrap_or(CustomColor {
+            r: 57,
+            g: 211,
+            b: 83,
+        }),
+        _ => return None,
+    };
+    Some(color)
+}
 
+/// Joins each line's label and value with the configured separators, bolding
+/// the title and the field labels in the configured info color.
+pub fn colorize_info(info: &[InfoLine], config: &Config, colors: bool) -> Vec<String> {
     // Define default text colors
     let default_text_colors = TextColors {
         info_color: "0,255,255".to_string(), // Cyan
     };
+    let default_info_config = InfoConfig::default();
 
     // Get text colors from config or use defaults
     let text_colors = config.text_colors.as_ref().unwrap_or(&default_text_colors);
+    let info_config = config.info.as_ref().unwrap_or(&default_info_config);
 
-    // Print colored info
-    for (index, info_line) in info.iter().enumerate() {
-        if index == 0 {
-            let parts: Vec<&str> = info_line.split('@').collect();
-            print!(
-                "{}@",
-                parts[0]
-                    .custom_color(get_color(&text_colors.info_color).unwrap_or(CustomColor {
+    info.iter()
+        .map(|line| {
+            let separator = line.separator(info_config);
+            if !colors {
+                return format!("{}{}{}", line.label, separator, line.value);
+            }
+            match line.style {
+                InfoStyle::Title => {
+                    let color = get_color(&text_colors.info_color).unwrap_or(CustomColor {
                         r: 255,
                         g: 255,
-                        b: 255
-                    }))
-                    .bold()
-            );
-            println!(
-                "{}",
-                parts[1]
-                    .custom_color(get_color(&text_colors.info_color).unwrap_or(CustomColor {
-                        r: 255,
+                        b: 255,
+                    });
+                    format!(
+                        "{}{}{}",
+                        line.label.custom_color(color).bold(),
+                        separator,
+                        line.value.custom_color(color).bold()
+                    )
+                }
+                InfoStyle::Field => {
+                    let color = get_color(&text_colors.info_color).unwrap_or(CustomColor {
+                        r: 0,
                         g: 255,
-                        b: 255
-                    }))
-                    .bold()
-            );
-        } else {
-            let parts: Vec<&str> = info_line.split(": ").collect();
-            if parts.len() == 2 {
-                print!(
-                    "{}: ",
-                    parts[0]
-                        .custom_color(get_color(&text_colors.info_color).unwrap_or(CustomColor {
-                            r: 0,
-                            g: 255,
-                            b: 255
-                        }))
-                        .bold()
-                );
-                println!("{}", parts[1]);
-            } else {
-                println!("{}", info_line);
+                        b: 255,
+                    });
+                    format!(
+                        "{}{}{}",
+                        line.label.custom_color(color).bold(),
+                        separator,
+                        line.value
+                    )
+                }
             }
-        }
-    }
+        })
+        .collect()
 }
 
+/// Groups week columns by month, each week counting towards the month of its
+/// first day in range. Returns `(month index, number of weeks)` pairs.
 fn calculate_month_spans(
     start_date: NaiveDate,
-    end_date: NaiveDate,
+    grid_start: NaiveDate,
     num_weeks: usize,
-    year_specified: bool,
 ) -> Vec<(usize, usize)> {
-    let mut month_spans = Vec::new();
-    let mut current_date = start_date;
-    let mut span_start = 0;
-    let mut current_month = (current_date.year(), current_date.month0() as usize);
-
+    let mut month_spans: Vec<(usize, usize)> = Vec::new();
     for week in 0..num_weeks {
-        let week_start_month = (current_date.year(), current_date.month0() as usize);
-        let week_end = current_date + Duration::days(6);
-
-        if week_start_month != current_month || (year_specified && week == num_weeks - 1) {
-            // Month changed or last week reached
-            let span = week - span_start;
-            if span > 0 {
-                month_spans.push((current_month.1, span));
-            }
-
-            if year_specified && week_start_month != current_month {
-                // If year is specified and month changed, add empty spans for skipped months
-                let mut next_month = (current_month.1 + 1) % 12;
-                while next_month != week_start_month.1 {
-                    month_spans.push((next_month, 0));
-                    next_month = (next_month + 1) % 12;
-                }
-            }
-
-            current_month = week_start_month;
-            span_start = week;
-        }
-
-        current_date = week_end + Duration::days(1);
-        if current_date > end_date {
-            break;
+        let first_day = (grid_start + Duration::days(week as i64 * 7)).max(start_date);
+        let month = first_day.month0() as usize;
+        match month_spans.last_mut() {
+            Some((last, span)) if *last == month => *span += 1,
+            _ => month_spans.push((month, 1)),
         }
     }
-
-    // // Add the last month if it's not empty
-    if span_start < num_weeks {
-        month_spans.push((current_month.1, num_weeks - span_start));
-    }
-
-    // Ensure all months are represented when year is specified
-    if year_specified {
-        let mut full_year_spans = vec![(0, 0); 12];
-        for (month, span) in month_spans {
-            full_year_spans[month] = (month, span);
-        }
-        month_spans = full_year_spans;
-    }
-    // Remove the first index if the span is lesser than 3
-    if !year_specified {
-        if let Some(&(_, span)) = month_spans.first() {
-            if span < 3 {
-                month_spans.remove(0);
-            }
-        }
-    }
-
-    // Ensure we don't have an empty graph if the date range is too short
-    if month_spans.is_empty() {
-        month_spans.push((start_date.month0() as usize, 1));
-    }
     month_spans
 }
 
@@ -273,3 +570,132 @@ fn get_color(color_str: &str) -> Result<CustomColor, anyhow::Error> {
         b: parts[2].parse()?,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_manager::WeekStart;
+
+    fn two_weeks() -> Vec<(NaiveDate, u32)> {
+        (1..=14)
+            .map(|day| (NaiveDate::from_ymd_opt(2024, 1, day).unwrap(), day % 5))
+            .collect()
+    }
+
+    #[test]
+    fn contribution_graph_places_days_under_their_weekday() {
+        // January 1st, 2024 was a Monday, so the Sunday row starts blank
+        let graph = generate_contribution_graph(&two_weeks(), true, &Config::default());
+        assert_eq!(
+            graph,
+            "    Jan   \n      ░░▓▓\nMon ░░▒▒  \n    ░░▓▓  \nWed ▒▒    \n    ▓▓░░  \nFri   ░░  \n    ░░▒▒  \n\nLess   ░░  ▒▒  ▓▓  ██ More\n"
+        );
+    }
+
+    #[test]
+    fn vertical_graph_puts_weeks_in_rows_and_months_in_the_margin() {
+        // Thursday, January 25th to Thursday, February 8th, 2024
+        let days: Vec<(NaiveDate, u32)> = NaiveDate::from_ymd_opt(2024, 1, 25)
+            .unwrap()
+            .iter_days()
+            .zip([1, 2, 3, 4, 0, 1, 2, 3, 4, 0, 1, 2, 3, 4, 0])
+            .collect();
+        let config = Config {
+            orientation: Some(Orientation::Vertical),
+            ..Config::default()
+        };
+        // Weeks start on Sunday, so the first row is blank up to Thursday and
+        // February is named on the first week that starts in it
+        let graph = generate_contribution_graph(&days, true, &config);
+        assert_eq!(
+            graph,
+            "    S M T W T F S \nJan         ░░░░▒▒\n    ▓▓  ░░░░▒▒▓▓  \nFeb ░░░░▒▒▓▓      \n\nLess   ░░  ▒▒  ▓▓  ██ More\n"
+        );
+
+        let config = Config {
+            week_start: Some(WeekStart::Monday),
+            ..config
+        };
+        let graph = generate_contribution_graph(&days, true, &config);
+        let rows: Vec<&str> = graph.lines().collect();
+        assert_eq!(rows[0], "    M T W T F S S ");
+        assert_eq!(rows[1], "Jan       ░░░░▒▒▓▓");
+    }
+
+    #[test]
+    fn contribution_graph_can_start_weeks_on_monday() {
+        let config = Config {
+            week_start: Some(WeekStart::Monday),
+            ..Config::default()
+        };
+        let graph = generate_contribution_graph(&two_weeks(), true, &config);
+        let rows: Vec<&str> = graph.lines().collect();
+        assert_eq!(rows[1], "Mon ░░▒▒");
+    }
+
+    #[test]
+    fn logo_markers_switch_colors_and_carry_across_lines() {
+        let art = "plain $1low$4 high\n$$5 still high\n$2$3mid";
+        let segments = |text: &[(Option<char>, &str)]| {
+            text.iter()
+                .map(|&(level, text)| (level, text.to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            logo_segments(art),
+            [
+                segments(&[(None, "plain "), (Some('░'), "low"), (Some('█'), " high")]),
+                segments(&[(Some('█'), "$$5 still high")]),
+                segments(&[(Some('▓'), "mid")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn colored_graph_without_colors_is_plain_text() {
+        let info = [
+            InfoLine::title("octocat", "2024"),
+            InfoLine::field("Total contributions", 3),
+        ];
+        assert_eq!(
+            render_colored_graph("    Jan\n░░██", &info, &Config::default(), false),
+            "    Jan\n░░██\n\noctocat@2024\nTotal contributions: 3\n"
+        );
+    }
+
+    #[test]
+    fn highlighted_days_are_marked_without_colors() {
+        let graph = generate_highlighted_graph(
+            &two_weeks(),
+            true,
+            |date| date == NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+            &Config::default(),
+            false,
+        );
+        let rows: Vec<&str> = graph.lines().collect();
+        // January 2nd, a Tuesday, had 2 contributions
+        assert_eq!(rows[3], "    >░▓▓  ");
+        assert!(!graph.contains('\x1b'));
+    }
+
+    #[test]
+    fn diverging_graph_without_colors_uses_ascii_for_the_second_user() {
+        let day = |day, count| (NaiveDate::from_ymd_opt(2024, 1, day).unwrap(), count);
+        let base = [day(1, 4), day(2, 1), day(3, 2)];
+        let compare = [day(1, 1), day(2, 4), day(3, 2)];
+        let graph = generate_diverging_graph(
+            &base,
+            &compare,
+            ("alice", "bob"),
+            true,
+            &Config::default(),
+            false,
+        );
+        let rows: Vec<&str> = graph.lines().collect();
+        assert_eq!(rows[2], "Mon ░░");
+        assert_eq!(rows[3], "    ..");
+        assert_eq!(rows[4], "Wed ··");
+        assert_eq!(rows.last().unwrap(), &"alice ██  bob ##  tie ··");
+        assert!(!graph.contains('\x1b'));
+    }
+}
@@ -0,0 +1,389 @@
+use crate::config_manager::{Config, InfoConfig, StatsConfig};
+use crate::contribution_analyzer::{calculate_extended_stats, ContributionData, ExtendedStats};
+use crate::contribution_source::ContributionSource;
+use crate::errors::FetchError;
+use crate::profile::{account_age, Profile};
+use crate::timezone;
+
+/// Every field `[info] fields` can list.
+pub const FIELDS: [&str; 24] = [
+    "title",
+    "name",
+    "bio",
+    "company",
+    "location",
+    "followers",
+    "following",
+    "repositories",
+    "stars",
+    "account_age",
+    "total",
+    "longest_streak",
+    "current_streak",
+    "max_contributions",
+    "active_days",
+    "average_per_active_day",
+    "median",
+    "busiest_weekday",
+    "busiest_month",
+    "longest_gap",
+    "weekend_ratio",
+    "rolling_7_day_average",
+    "rolling_30_day_average",
+    "data_source",
+];
+
+/// The fields that need the user's profile, fetched with an extra request.
+const PROFILE_FIELDS: [&str; 9] = [
+    "name",
+    "bio",
+    "company",
+    "location",
+    "followers",
+    "following",
+    "repositories",
+    "stars",
+    "account_age",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// How a line of the info panel is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfoStyle {
+    /// The label and value joined by the title separator, both highlighted,
+    /// like `octocat@2024`.
+    Title,
+    /// The label and value joined by the separator, the label highlighted.
+    Field,
+}
+
+/// One line of the info panel.
+#[derive(Debug, Clone)]
+pub struct InfoLine {
+    pub label: String,
+    pub value: String,
+    pub style: InfoStyle,
+}
+
+impl InfoLine {
+    pub fn title(name: impl Into<String>, context: impl Into<String>) -> Self {
+        InfoLine {
+            label: name.into(),
+            value: context.into(),
+            style: InfoStyle::Title,
+        }
+    }
+
+    pub fn field(label: impl Into<String>, value: impl ToString) -> Self {
+        InfoLine {
+            label: label.into(),
+            value: value.to_string(),
+            style: InfoStyle::Field,
+        }
+    }
+
+    /// What goes between the label and the value.
+    pub fn separator<'a>(&self, info_config: &'a InfoConfig) -> &'a str {
+        match self.style {
+            InfoStyle::Title => &info_config.title_separator,
+            InfoStyle::Field => &info_config.separator,
+        }
+    }
+
+    /// The line without colors, for tooltips and the like.
+    pub fn to_plain(&self, config: &Config) -> String {
+        let default_info_config = InfoConfig::default();
+        let info_config = config.info.as_ref().unwrap_or(&default_info_config);
+        format!(
+            "{}{}{}",
+            self.label,
+            self.separator(info_config),
+            self.value
+        )
+    }
+}
+
+/// The info panel for one user's contributions, with the fields listed
+/// under `[info]`. Fields that don't apply, like a busiest month without any
+/// contributions or a company without a profile, are left out.
+pub fn contribution_info(
+    username: &str,
+    year: &str,
+    contributions: &ContributionData,
+    profile: Option<&Profile>,
+    config: &Config,
+) -> Vec<InfoLine> {
+    let default_info_config = InfoConfig::default();
+    let info_config = config.info.as_ref().unwrap_or(&default_info_config);
+    let fields = match &info_config.fields {
+        Some(fields) => fields.iter().map(String::as_str).collect(),
+        None => default_fields(config.stats.as_ref()),
+    };
+
+    let stats = calculate_extended_stats(&contributions.daily_contributions);
+    fields
+        .into_iter()
+        .filter_map(|name| {
+            let mut line = if PROFILE_FIELDS.contains(&name) {
+                profile_field(name, profile?)?
+            } else {
+                field(name, username, year, contributions, &stats)?
+            };
+            if let Some(label) = info_config.labels.get(name) {
+                if line.style == InfoStyle::Field {
+                    line.label = label.clone();
+                }
+            }
+            Some(line)
+        })
+        .collect()
+}
+
+/// Fetches the profile if the panel shows any of its fields.
+pub async fn load_profile(
+    source: &ContributionSource,
+    username: &str,
+    config: &Config,
+) -> Result<Option<Profile>, FetchError> {
+    if !shows_profile(config) {
+        return Ok(None);
+    }
+    source.profile(username).await
+}
+
+/// Whether `[info] fields` lists any profile field.
+pub fn shows_profile(config: &Config) -> bool {
+    config
+        .info
+        .as_ref()
+        .and_then(|info| info.fields.as_ref())
+        .is_some_and(|fields| {
+            fields
+                .iter()
+                .any(|name| PROFILE_FIELDS.contains(&name.as_str()))
+        })
+}
+
+/// The names in `[info] fields` that aren't fields.
+pub fn unknown_fields(config: &Config) -> Vec<&str> {
+    config
+        .info
+        .as_ref()
+        .and_then(|info| info.fields.as_ref())
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .filter(|name| !FIELDS.contains(name))
+        .collect()
+}
+
+/// The title and totals, the stats enabled under `[stats]`, and where the
+/// data came from when it's limited.
+fn default_fields(stats: Option<&StatsConfig>) -> Vec<&'static str> {
+    let mut fields = vec![
+        "title",
+        "total",
+        "longest_streak",
+        "current_streak",
+        "max_contributions",
+    ];
+    if let Some(stats) = stats {
+        let enabled = [
+            ("active_days", stats.active_days),
+            ("average_per_active_day", stats.average_per_active_day),
+            ("median", stats.median),
+            ("busiest_weekday", stats.busiest_weekday),
+            ("busiest_month", stats.busiest_month),
+            ("longest_gap", stats.longest_gap),
+            ("weekend_ratio", stats.weekend_ratio),
+            ("rolling_7_day_average", stats.rolling_7_day_average),
+            ("rolling_30_day_average", stats.rolling_30_day_average),
+        ];
+        fields.extend(
+            enabled
+                .into_iter()
+                .filter(|&(_, enabled)| enabled)
+                .map(|(name, _)| name),
+        );
+    }
+    fields.push("data_source");
+    fields
+}
+
+fn profile_field(name: &str, profile: &Profile) -> Option<InfoLine> {
+    let line = match name {
+        "name" => InfoLine::field("Name", profile.name.as_ref()?),
+        "bio" => InfoLine::field("Bio", profile.bio.as_ref()?),
+        "company" => InfoLine::field("Company", profile.company.as_ref()?),
+        "location" => InfoLine::field("Location", profile.location.as_ref()?),
+        "followers" => InfoLine::field("Followers", profile.followers),
+        "following" => InfoLine::field("Following", profile.following),
+        "repositories" => InfoLine::field("Public Repositories", profile.public_repositories),
+        "stars" => InfoLine::field("Stars", profile.stars),
+        "account_age" => InfoLine::field(
+            "Account Age",
+            account_age(profile.created_at, timezone::today()),
+        ),
+        _ => return None,
+    };
+    Some(line)
+}
+
+fn field(
+    name: &str,
+    username: &str,
+    year: &str,
+    contributions: &ContributionData,
+    stats: &ExtendedStats,
+) -> Option<InfoLine> {
+    let line = match name {
+        "title" => InfoLine::title(username, year),
+        "total" => InfoLine::field("Total contributions", contributions.total),
+        "longest_streak" => InfoLine::field(
+            "Longest Streak",
+            format!("{} days", contributions.longest_streak),
+        ),
+        "current_streak" => InfoLine::field(
+            "Current Streak",
+            format!("{} days", contributions.current_streak),
+        ),
+        "max_contributions" => InfoLine::field(
+            "Most Contributions in a Day",
+            contributions.max_contributions,
+        ),
+        "active_days" => InfoLine::field(
+            "Active Days",
+            format!(
+                "{}/{} ({:.1}%)",
+                stats.active_days,
+                stats.total_days,
+                stats.active_percentage()
+            ),
+        ),
+        "average_per_active_day" => InfoLine::field(
+            "Average per Active Day",
+            format!("{:.1}", stats.average_per_active_day),
+        ),
+        "median" => InfoLine::field("Median per Active Day", format!("{:.1}", stats.median)),
+        "busiest_weekday" => {
+            let (weekday, total) = stats.busiest_weekday?;
+            InfoLine::field(
+                "Busiest Weekday",
+                format!(
+                    "{} ({})",
+                    WEEKDAYS[weekday.num_days_from_monday() as usize],
+                    total
+                ),
+            )
+        }
+        "busiest_month" => {
+            let (month, total) = stats.busiest_month?;
+            InfoLine::field("Busiest Month", format!("{} ({})", MONTHS[month], total))
+        }
+        "longest_gap" => InfoLine::field("Longest Gap", format!("{} days", stats.longest_gap)),
+        "weekend_ratio" => InfoLine::field(
+            "Weekend/Weekday Ratio",
+            match stats.weekend_ratio {
+                Some(ratio) => format!("{:.2}", ratio),
+                None => "n/a".to_string(),
+            },
+        ),
+        "rolling_7_day_average" => InfoLine::field(
+            "7-Day Average",
+            format!("{:.1}", stats.rolling_7_day_average),
+        ),
+        "rolling_30_day_average" => InfoLine::field(
+            "30-Day Average",
+            format!("{:.1}", stats.rolling_30_day_average),
+        ),
+        "data_source" if contributions.limited => {
+            InfoLine::field("Data", "public profile only (limited)")
+        }
+        _ => return None,
+    };
+    Some(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contribution_analyzer::summarize_contributions;
+    use chrono::NaiveDate;
+
+    fn data() -> ContributionData {
+        let daily = (1..=3)
+            .map(|day| (NaiveDate::from_ymd_opt(2024, 1, day).unwrap(), day))
+            .collect();
+        summarize_contributions(6, daily)
+    }
+
+    fn plain(info: &[InfoLine], config: &Config) -> Vec<String> {
+        info.iter().map(|line| line.to_plain(config)).collect()
+    }
+
+    #[test]
+    fn fields_follow_the_configured_order_labels_and_separators() {
+        let config = Config {
+            info: Some(InfoConfig {
+                fields: Some(vec!["total".into(), "title".into(), "bogus".into()]),
+                labels: [("total".to_string(), "Sum".to_string())].into(),
+                separator: " = ".into(),
+                title_separator: " in ".into(),
+            }),
+            ..Config::default()
+        };
+        let info = contribution_info("user@example", "2024", &data(), None, &config);
+        assert_eq!(plain(&info, &config), ["Sum = 6", "user@example in 2024"]);
+    }
+
+    #[test]
+    fn profile_fields_are_left_out_without_a_profile() {
+        let config = Config {
+            info: Some(InfoConfig {
+                fields: Some(vec!["name".into(), "total".into()]),
+                ..InfoConfig::default()
+            }),
+            ..Config::default()
+        };
+        let info = contribution_info("octocat", "2024", &data(), None, &config);
+        assert_eq!(plain(&info, &config), ["Total contributions: 6"]);
+    }
+
+    #[test]
+    fn limited_data_is_noted_by_default() {
+        let config = Config::default();
+        let mut data = data();
+        let shown = |data: &ContributionData| {
+            contribution_info("octocat", "2024", data, None, &config)
+                .iter()
+                .any(|line| line.label == "Data")
+        };
+        assert!(!shown(&data));
+        data.limited = true;
+        assert!(shown(&data));
+    }
+}
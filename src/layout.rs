@@ -0,0 +1,55 @@
+/// Columns between art, like an avatar or a logo, and the text beside it.
+pub const GAP: usize = 2;
+
+/// Width of `line` in terminal columns, not counting ANSI escape sequences.
+pub fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip to the final byte of the CSI sequence, like the `m` of a color
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Puts `right` beside `left`, padding the left column to its widest line
+/// plus `gap` so the right one lines up.
+pub fn side_by_side(left: &[String], right: &[String], gap: usize) -> Vec<String> {
+    let width = left
+        .iter()
+        .map(|line| visible_width(line))
+        .max()
+        .unwrap_or(0);
+    (0..left.len().max(right.len()))
+        .map(|row| {
+            let left_line = left.get(row).map_or("", String::as_str);
+            match right.get(row) {
+                Some(right_line) => format!(
+                    "{}{}{}",
+                    left_line,
+                    " ".repeat(width - visible_width(left_line) + gap),
+                    right_line
+                ),
+                None => left_line.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// `side_by_side` with the usual gap, as one string.
+pub fn beside(left: &[String], right: &[String]) -> String {
+    side_by_side(left, right, GAP)
+        .into_iter()
+        .map(|line| line + "\n")
+        .collect()
+}
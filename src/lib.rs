@@ -0,0 +1,55 @@
+//! Fetches, analyzes and renders GitHub contribution calendars, as used by
+//! the `gitfetch` command.
+//!
+//! Data comes from a [`contribution_source::ContributionSource`], backed by
+//! the GraphQL API or the public profile page, as
+//! [`contribution_analyzer::ContributionData`]. The renderers in
+//! [`graph_generator`], [`info`], [`compare`], [`status_line`] and [`day`]
+//! return strings and leave printing to the caller. The subcommands of
+//! `gitfetch` are in [`commands`], returning their text and warnings.
+//!
+//! ```no_run
+//! use gitfetch::config_manager::Config;
+//! use gitfetch::contribution_source::{ContributionSource, Provider};
+//! use gitfetch::graph_generator::{generate_contribution_graph, render_colored_graph};
+//! use gitfetch::info::contribution_info;
+//!
+//! # async fn example() -> Result<(), gitfetch::errors::FetchError> {
+//! let config = Config::default();
+//! let source = ContributionSource::new(Provider::PublicProfile, true);
+//! let data = source.fetch("octocat", "2024", true).await?;
+//!
+//! let graph = generate_contribution_graph(&data.daily_contributions, true, &config);
+//! let info = contribution_info("octocat", "2024", &data, None, &config);
+//! print!("{}", render_colored_graph(&graph, &info, &config, true));
+//! # Ok(())
+//! # }
+//! ```
+
+pub(crate) mod avatar;
+pub mod color;
+pub mod commands;
+pub mod compare;
+pub mod config_manager;
+pub mod contribution_analyzer;
+pub mod contribution_source;
+pub mod day;
+pub mod errors;
+pub mod github_client;
+pub mod graph_generator;
+pub mod info;
+pub(crate) mod layout;
+pub(crate) mod local_git;
+pub(crate) mod logo;
+pub mod profile;
+pub(crate) mod public_profile;
+pub mod punchcard;
+pub(crate) mod rate_limit;
+pub mod status_line;
+pub mod team;
+pub mod timezone;
+pub(crate) mod token_resolver;
+pub(crate) mod tui;
+pub(crate) mod watch;
+
+pub use rate_limit::set_verbose;
@@ -0,0 +1,80 @@
+use crate::day::LocalCommit;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, FixedOffset};
+use std::path::Path;
+use std::process::Command;
+
+/// Author timestamps of the commits in `repo` whose author name or email
+/// matches `author`, across all refs, between the instants `from` and `to`.
+pub fn commit_times(
+    repo: &Path,
+    author: &str,
+    from: DateTime<FixedOffset>,
+    to: DateTime<FixedOffset>,
+) -> Result<Vec<DateTime<FixedOffset>>> {
+    log(repo, author, from, to, "%aI")?
+        .lines()
+        .map(parse_date)
+        .collect()
+}
+
+/// Like `commit_times`, with each commit's short hash and subject.
+pub fn commits(
+    repo: &Path,
+    author: &str,
+    from: DateTime<FixedOffset>,
+    to: DateTime<FixedOffset>,
+) -> Result<Vec<LocalCommit>> {
+    log(repo, author, from, to, "%aI%x09%h%x09%s")?
+        .lines()
+        .map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let (Some(time), Some(hash), Some(subject)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                bail!("Unexpected line in git log output: {}", line);
+            };
+            Ok(LocalCommit {
+                time: parse_date(time)?,
+                hash: hash.to_string(),
+                subject: subject.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn log(
+    repo: &Path,
+    author: &str,
+    from: DateTime<FixedOffset>,
+    to: DateTime<FixedOffset>,
+    format: &str,
+) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args([
+            "log",
+            "--all",
+            "--no-merges",
+            &format!("--author={}", author),
+            &format!("--since={}", from.to_rfc3339()),
+            &format!("--until={}", to.to_rfc3339()),
+            &format!("--format={}", format),
+        ])
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git log failed in {}: {}",
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn parse_date(date: &str) -> Result<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(date)
+        .with_context(|| format!("Unexpected date in git log output: {}", date))
+}
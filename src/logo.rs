@@ -0,0 +1,87 @@
+use crate::config_manager::LogoConfig;
+use anyhow::{bail, Context, Result};
+use std::fs;
+
+// `$1` to `$4` pick the colors of the graph levels, see `colorize_logo`
+
+const GITHUB: &str = r"$4       .:::::::::::.
+    .:::::::::::::::::.
+  .:::. ':::::::::' .:::.
+ ::::'               '::::
+.::::                 ::::.
+:::::                 :::::
+:::::                 :::::
+':::::               :::::'
+ '::::::.         .::::::'
+  ':: '::::     ::::::::'
+    '::..::     :::::'
+       ':::     :::'";
+
+const GITLAB: &str = r"$3 ##                  ##
+####                ####
+######            ######
+$4########$2########$4########
+ #######$2########$4#######
+   #####$2########$4#####
+      ##$2########$4##
+$2         ######
+           ##";
+
+const GIT: &str = r"$4            ##
+          ######
+        ##########
+      ######  ######
+    ########  ##  ####
+  ##########    ########
+    ########  ########
+      ######  ######
+        ##########
+          ######
+            ##";
+
+/// The logo's art with color markers, or `None` if none is configured.
+/// A custom file takes precedence over a named logo.
+pub fn load(logo_config: &LogoConfig) -> Result<Option<String>> {
+    if let Some(path) = &logo_config.path {
+        let art = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read logo file: {}", path))?;
+        return Ok(Some(art));
+    }
+    let art = match logo_config.name.as_deref() {
+        None => return Ok(None),
+        Some("github") => GITHUB,
+        Some("gitlab") => GITLAB,
+        Some("git") => GIT,
+        Some(name) => bail!("Unknown logo '{}', expected github, gitlab or git", name),
+    };
+    Ok(Some(art.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/logo.txt");
+
+    fn logo(name: Option<&str>, path: Option<&str>) -> Result<Option<String>> {
+        load(&LogoConfig {
+            name: name.map(str::to_string),
+            path: path.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn custom_art_takes_precedence_over_the_name() {
+        let art = logo(Some("gitlab"), Some(FIXTURE)).unwrap().unwrap();
+        assert_eq!(art, include_str!("../tests/fixtures/logo.txt"));
+        assert!(logo(Some("github"), Some("/nonexistent/logo.txt")).is_err());
+    }
+
+    #[test]
+    fn built_in_logos_are_picked_by_name() {
+        assert_eq!(logo(Some("git"), None).unwrap().as_deref(), Some(GIT));
+        assert_eq!(logo(None, None).unwrap(), None);
+        let error = logo(Some("octocat"), None).unwrap_err();
+        assert!(error.to_string().contains("Unknown logo 'octocat'"));
+    }
+}
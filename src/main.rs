@@ -1,110 +1,327 @@
 use anyhow::Result;
-mod contribution_analyzer;
-use chrono::Local;
-mod errors;
-mod github_client;
-mod graph_generator;
-use clap::{arg, Command as clapCommand};
-use core::result::Result::Ok;
+use chrono::{Datelike, NaiveDate};
+use clap::{arg, ArgMatches, Command as clapCommand};
+use commands::{Options, Output};
 use errors::FetchError;
-use github_client::fetch_contributions;
-use graph_generator::{generate_contribution_graph, print_colored_graph};
-use octocrab::Octocrab;
+use gitfetch::{color, commands, config_manager, errors, github_client, info, timezone};
+use std::path::Path;
 use std::process::Command;
 
-mod config_manager;
 #[tokio::main]
 async fn main() -> Result<()> {
     let matches = clapCommand::new("gitfetch")
         .version("0.1.0")
         .about("Fetch and display GitHub contributions")
         .arg(arg!(-u --username <VALUE>).required(false))
-        .arg(arg!(-y --year <VALUE>).required(false))
+        .arg(arg!(-y --year <VALUE>).required(false).global(true))
+        .arg(arg!(--compare <USER> "Compare with another user").required(false))
+        .arg(
+            arg!(--diverging "With --compare, color each day by who contributed more")
+                .requires("compare"),
+        )
+        .arg(
+            arg!(--"vs-year" <YEAR> "Compare the selected year with another year")
+                .required(false)
+                .conflicts_with("compare"),
+        )
+        .arg(
+            arg!(--format <FORMAT> "Print the graph, a one-line summary, or waybar JSON")
+                .required(false)
+                .value_parser(["graph", "line", "waybar"])
+                .default_value("graph")
+                .conflicts_with_all(["compare", "vs-year"]),
+        )
+        .arg(
+            arg!(--watch <INTERVAL> "Keep refreshing, e.g. every 5m, highlighting new contributions")
+                .required(false)
+                .conflicts_with_all(["compare", "vs-year"]),
+        )
+        .arg(arg!(-v --verbose "Report retries and remaining API quota").global(true))
+        .arg(
+            arg!(-c --config <PATH> "Path to the config file")
+                .required(false)
+                .global(true),
+        )
+        .arg(
+            arg!(--host <HOST> "GitHub host, e.g. a GitHub Enterprise instance")
+                .required(false)
+                .global(true)
+                .default_value(config_manager::DEFAULT_HOST),
+        )
+        .arg(
+            arg!(--orientation <LAYOUT> "Lay weeks out as columns or as rows")
+                .required(false)
+                .global(true)
+                .value_parser(["horizontal", "vertical"]),
+        )
+        .arg(
+            arg!(--color <WHEN> "Color the output: auto, always or never")
+                .required(false)
+                .global(true)
+                .value_parser(["auto", "always", "never"])
+                .default_value("auto"),
+        )
+        .arg(
+            arg!(--tz <ZONE> "Time zone to count days in, e.g. Europe/Berlin or +02:00")
+                .required(false)
+                .global(true),
+        )
+        .subcommand(add_token_command("add-token"))
+        .subcommand(
+            clapCommand::new("token")
+                .about("Manage GitHub access tokens")
+                .subcommand_required(true)
+                .subcommand(add_token_command("add"))
+                .subcommand(clapCommand::new("remove").about("Remove the stored token"))
+                .subcommand(
+                    clapCommand::new("status")
+                        .about("Validate the token and show its login, scopes and rate limit"),
+                ),
+        )
+        .subcommand(
+            clapCommand::new("team")
+                .about("Rank a team by contributions and show its combined heatmap")
+                .arg(arg!(--users <LIST> "Comma-separated usernames").required(false))
+                .arg(arg!(--file <PATH> "File with one username per line").required(false))
+                .arg(arg!(--org <ORG> "Organization of the team to fetch").required(false))
+                .arg(
+                    arg!(--team <SLUG> "Slug of the organization team")
+                        .required(false)
+                        .requires("org"),
+                ),
+        )
+        .subcommand(
+            clapCommand::new("punchcard")
+                .about("Show commits by weekday and hour of day")
+                .arg(arg!(-u --username <VALUE>).required(false))
+                .arg(
+                    arg!(--repo <PATH> "Read commits from a local git repository").required(false),
+                ),
+        )
         .subcommand(
-            clapCommand::new("add-token")
-                .about("Add GitHub access token")
-                .arg(arg!(<TOKEN> "GitHub access token")),
+            clapCommand::new("tui")
+                .about("Browse the heatmap interactively")
+                .arg(arg!(-u --username <VALUE>).required(false)),
+        )
+        .subcommand(
+            clapCommand::new("day")
+                .about("List the commits, pull requests, issues and reviews of one day")
+                .arg(arg!(<DATE> "Day to show, as YYYY-MM-DD"))
+                .arg(arg!(-u --username <VALUE>).required(false))
+                .arg(
+                    arg!(--repo <PATH> "Also list commits from a local git repository")
+                        .required(false),
+                ),
         )
         .get_matches();
+    match run(&matches).await {
+        Ok(()) => Ok(()),
+        Err(e) => match e.downcast_ref::<FetchError>() {
+            Some(e) => exit_with_error(e),
+            None => Err(e),
+        },
+    }
+}
+
+async fn run(matches: &ArgMatches) -> Result<()> {
+    let config_path = matches.get_one::<String>("config").map(Path::new);
+    gitfetch::set_verbose(matches.get_flag("verbose"));
+    color::set_choice(matches.get_one::<String>("color").unwrap());
+    let host = matches.get_one::<String>("host").unwrap();
     if let Some(matches) = matches.subcommand_matches("add-token") {
         let token = matches.get_one::<String>("TOKEN").unwrap();
-        return config_manager::add_token(token);
-    }
-
-    let config = config_manager::read_config()?;
-    let username = match matches.get_one::<String>("username") {
-        Some(name) => name.to_string(),
-        None => match get_git_global_username() {
-            Some(name) => name,
-            None => {
-                eprintln!("Error: No username provided and couldn't fetch git global user.");
-                eprintln!("Please provide a username with -u or set your git global user.name");
-                std::process::exit(1);
+        print_output(commands::add_token(
+            token,
+            host,
+            matches.get_flag("plaintext"),
+            config_path,
+        )?);
+        return Ok(());
+    }
+
+    let mut config = config_manager::read_config(config_path)?;
+    match matches.get_one::<String>("orientation").map(String::as_str) {
+        Some("vertical") => config.orientation = Some(config_manager::Orientation::Vertical),
+        Some(_) => config.orientation = Some(config_manager::Orientation::Horizontal),
+        None => {}
+    }
+    for name in info::unknown_fields(&config) {
+        eprintln!(
+            "Warning: unknown info field '{}', expected one of {}.",
+            name,
+            info::FIELDS.join(", ")
+        );
+    }
+    if let Some(matches) = matches.subcommand_matches("token") {
+        let output = match matches.subcommand() {
+            Some(("add", matches)) => {
+                let token = matches.get_one::<String>("TOKEN").unwrap();
+                commands::add_token(token, host, matches.get_flag("plaintext"), config_path)?
             }
-        },
-    };
+            Some(("remove", _)) => commands::remove_token(&config, host, config_path)?,
+            Some(("status", _)) => commands::token_status(&config, host).await?,
+            _ => unreachable!("clap requires a token subcommand"),
+        };
+        print_output(output);
+        return Ok(());
+    }
+
+    if let Some(name) = matches.get_one::<String>("tz").or(config.timezone.as_ref()) {
+        let zone = timezone::Zone::parse(name).ok_or_else(|| {
+            FetchError::InvalidInput(format!(
+                "'{}' is not a time zone name like Europe/Berlin or an offset like +02:00",
+                name
+            ))
+        })?;
+        timezone::set_zone(zone);
+    }
 
     let year_specified = matches.contains_id("year");
     let year = matches
         .get_one::<String>("year")
         .cloned()
-        .unwrap_or_else(|| Local::now().format("%Y").to_string());
+        .unwrap_or_else(|| timezone::today().year().to_string());
+    let options = Options {
+        config: &config,
+        year: &year,
+        year_specified,
+        colors: color::enabled(),
+    };
 
-    let token = match config.github_token {
-        Some(ref token) => token,
-        None => {
-            eprintln!("GitHub token not found in config. Please run 'gitfetch add-token <TOKEN>' to add your token.");
-            std::process::exit(1);
+    if let Some(matches) = matches.subcommand_matches("team") {
+        let mut members: Vec<String> = Vec::new();
+        if let Some(users) = matches.get_one::<String>("users") {
+            members.extend(users.split(',').map(|user| user.trim().to_string()));
         }
-    };
+        if let Some(path) = matches.get_one::<String>("file") {
+            members.extend(commands::read_team_file(Path::new(path))?);
+        }
+        let org_team = matches
+            .get_one::<String>("org")
+            .map(|org| (org.clone(), matches.get_one::<String>("team").cloned()));
+        let source = connect(&config, host)?;
+        print_output(commands::team(&source, members, org_team, &options).await?);
+        return Ok(());
+    }
 
-    let octocrab = Octocrab::builder()
-        .personal_token(token.to_string())
-        .build()?;
-
-    // Fetch contribution data
-    let contributions = match fetch_contributions(&username, &year, year_specified, octocrab).await
-    {
-        Ok(data) => data,
-        Err(e) => {
-            eprintln!("Error fetching contributions: {}", e);
-            match e {
-                FetchError::YearParseError(_) => {
-                    eprintln!("Invalid year format provided");
-                }
-                FetchError::DateCreationError => {
-                    eprintln!("Failed to create a valid date");
-                }
-                FetchError::GitHubApiError(api_error) => {
-                    eprintln!("GitHub API error: {}", api_error);
-                }
-                FetchError::UnexpectedResponseFormat => {
-                    eprintln!("Received unexpected response format from GitHub");
-                }
-            }
-            std::process::exit(1);
+    if let Some(matches) = matches.subcommand_matches("punchcard") {
+        let username = resolve_username(matches)?;
+        let repo = matches.get_one::<String>("repo").map(Path::new);
+        print_output(commands::punchcard(host, &username, repo, &options).await?);
+        return Ok(());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("day") {
+        let username = resolve_username(matches)?;
+        let date = matches.get_one::<String>("DATE").unwrap();
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+            FetchError::InvalidInput(format!("'{}' is not a date like 2024-03-14", date))
+        })?;
+        let repo = matches.get_one::<String>("repo").map(Path::new);
+        print_output(commands::day(&config, host, &username, date, repo).await?);
+        return Ok(());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("tui") {
+        let username = resolve_username(matches)?;
+        let year = if year_specified {
+            Some(github_client::parse_year(&year)?)
+        } else {
+            None
+        };
+        let source = connect(&config, host)?;
+        return commands::tui(&source, &config, username, year).await;
+    }
+
+    let username = resolve_username(matches)?;
+    if let Some(other_year) = matches.get_one::<String>("vs-year") {
+        let source = connect(&config, host)?;
+        print_output(commands::compare_years(&source, &username, other_year, &options).await?);
+        return Ok(());
+    }
+
+    if let Some(other) = matches.get_one::<String>("compare") {
+        let source = connect(&config, host)?;
+        let width = crossterm::terminal::size()
+            .ok()
+            .map(|(columns, _)| columns as usize);
+        let output = commands::compare_users(
+            &source,
+            (&username, other),
+            matches.get_flag("diverging"),
+            width,
+            &options,
+        )
+        .await?;
+        print_output(output);
+        return Ok(());
+    }
+
+    if let Some(interval) = matches.get_one::<String>("watch") {
+        let (interval, warning) = commands::watch_interval(interval)?;
+        print_warnings(warning);
+        let source = connect(&config, host)?;
+        return commands::watch(&source, &username, interval, &options).await;
+    }
+
+    let source = connect(&config, host)?;
+    let output = match matches.get_one::<String>("format").map(String::as_str) {
+        Some(format @ ("line" | "waybar")) => {
+            commands::status_line(&source, &username, format == "waybar", &options).await?
         }
+        _ => commands::summary(&source, &username, host, &options).await?,
     };
+    print_output(output);
+    Ok(())
+}
 
-    let graph =
-        generate_contribution_graph(&contributions.daily_contributions, year_specified, &config);
+/// Connects like `commands::connect`, printing why data may be limited.
+fn connect(
+    config: &config_manager::Config,
+    host: &str,
+) -> Result<gitfetch::contribution_source::ContributionSource> {
+    let (source, warning) = commands::connect(config, host)?;
+    print_warnings(warning);
+    Ok(source)
+}
 
-    // Prepare info text
-    let info = vec![
-        format!("{}@{}", username, year),
-        format!("Total contributions: {}", contributions.total),
-        format!("Longest Streak: {} days", contributions.longest_streak),
-        format!("Current Streak: {} days", contributions.current_streak),
-        format!(
-            "Most Contributions in a Day: {}",
-            contributions.max_contributions
-        ),
-    ];
+fn print_output(output: Output) {
+    print_warnings(output.warnings);
+    print!("{}", output.text);
+}
 
-    // Print colored graph
-    print_colored_graph(&graph, &info, &config);
+fn print_warnings(warnings: impl IntoIterator<Item = String>) {
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+}
 
-    Ok(())
+/// Prints the error with its suggested fix and exits with the error's code.
+fn exit_with_error(error: &FetchError) -> ! {
+    eprintln!("Error: {}", error);
+    if let Some(hint) = error.hint() {
+        eprintln!("Hint: {}", hint);
+    }
+    std::process::exit(error.exit_code());
+}
+
+fn add_token_command(name: &'static str) -> clapCommand {
+    clapCommand::new(name)
+        .about("Add GitHub access token")
+        .arg(arg!(<TOKEN> "GitHub access token"))
+        .arg(arg!(--plaintext "Store the token in the config file instead of the system keyring"))
+}
+
+/// Takes `-u` or falls back to the git global user.
+fn resolve_username(matches: &ArgMatches) -> Result<String, FetchError> {
+    match matches.get_one::<String>("username") {
+        Some(name) => Ok(name.to_string()),
+        None => get_git_global_username().ok_or_else(|| {
+            FetchError::InvalidInput(
+                "no username given with -u and no git global user.name is set".to_string(),
+            )
+        }),
+    }
 }
 
 fn get_git_global_username() -> Option<String> {
@@ -0,0 +1,83 @@
+use chrono::{Datelike, NaiveDate};
+
+/// Public details of a GitHub account.
+#[non_exhaustive]
+pub struct Profile {
+    pub name: Option<String>,
+    pub bio: Option<String>,
+    pub company: Option<String>,
+    pub location: Option<String>,
+    pub followers: u64,
+    pub following: u64,
+    pub public_repositories: u64,
+    /// Stars on the user's own public repositories, forks excluded.
+    pub stars: u64,
+    pub created_at: NaiveDate,
+}
+
+/// How long ago `created` was, in years and months, or in days for accounts
+/// younger than a month.
+pub fn account_age(created: NaiveDate, today: NaiveDate) -> String {
+    let mut months =
+        (today.year() - created.year()) * 12 + today.month() as i32 - created.month() as i32;
+    if today.day() < created.day() {
+        months -= 1;
+    }
+    if months < 1 {
+        let days = (today - created).num_days().max(0);
+        return plural(days, "day");
+    }
+    match (months / 12, months % 12) {
+        (0, months) => plural(months.into(), "month"),
+        (years, 0) => plural(years.into(), "year"),
+        (years, months) => format!(
+            "{}, {}",
+            plural(years.into(), "year"),
+            plural(months.into(), "month")
+        ),
+    }
+}
+
+fn plural(count: i64, unit: &str) -> String {
+    if count == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn age(created: (i32, u32, u32), today: (i32, u32, u32)) -> String {
+        let date = |(year, month, day)| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        account_age(date(created), date(today))
+    }
+
+    #[test]
+    fn young_accounts_are_counted_in_days() {
+        assert_eq!(age((2024, 5, 10), (2024, 5, 10)), "0 days");
+        assert_eq!(age((2024, 5, 10), (2024, 5, 11)), "1 day");
+        assert_eq!(age((2024, 5, 10), (2024, 6, 9)), "30 days");
+        assert_eq!(age((2024, 5, 10), (2024, 6, 10)), "1 month");
+    }
+
+    #[test]
+    fn whole_years_leave_out_the_months() {
+        assert_eq!(age((2020, 3, 15), (2021, 3, 15)), "1 year");
+        assert_eq!(age((2020, 3, 15), (2024, 3, 15)), "4 years");
+        assert_eq!(age((2020, 3, 15), (2024, 3, 14)), "3 years, 11 months");
+        assert_eq!(age((2020, 3, 15), (2025, 5, 20)), "5 years, 2 months");
+    }
+
+    #[test]
+    fn month_end_creation_dates_wait_for_the_day_to_come_around() {
+        // February has no 31st, so a month only passes on March 31st
+        assert_eq!(age((2024, 1, 31), (2024, 2, 29)), "29 days");
+        assert_eq!(age((2024, 1, 31), (2024, 3, 30)), "1 month");
+        assert_eq!(age((2024, 1, 31), (2024, 3, 31)), "2 months");
+        assert_eq!(age((2020, 2, 29), (2021, 2, 28)), "11 months");
+        assert_eq!(age((2020, 2, 29), (2021, 3, 1)), "1 year");
+    }
+}
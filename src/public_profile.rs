@@ -0,0 +1,159 @@
+use crate::contribution_analyzer::{summarize_contributions, ContributionData};
+use crate::errors::FetchError;
+use crate::github_client::{client_builder, parse_year};
+use crate::rate_limit::send_with_retry;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+/// Fetches contributions from the public calendar on the user's profile page.
+/// This needs no token, but only shows what the profile shows: private
+/// contributions are missing unless the user opted to display them.
+pub async fn fetch_public_contributions(
+    username: &str,
+    year: &str,
+    year_specified: bool,
+) -> Result<ContributionData, FetchError> {
+    let octocrab = client_builder().base_uri("https://github.com")?.build()?;
+
+    let mut url = format!("/users/{}/contributions", username);
+    if year_specified {
+        let year_int = parse_year(year)?;
+        url += &format!("?from={0}-01-01&to={0}-12-31", year_int);
+    }
+
+    let response = send_with_retry(|| octocrab._get(url.as_str())).await?;
+    match response.status().as_u16() {
+        404 => return Err(FetchError::UserNotFound(username.to_string())),
+        429 => return Err(FetchError::RateLimited { reset_at: None }),
+        status if !(200..300).contains(&status) => return Err(FetchError::PublicPageError(status)),
+        _ => {}
+    }
+    let html = octocrab.body_to_string(response).await?;
+
+    parse_contributions_page(&html)
+}
+
+/// Parses the contributions calendar markup into `ContributionData`.
+///
+/// Each day is a `<td class="ContributionCalendar-day" data-date=...>` cell,
+/// and its count lives in the text of the `<tool-tip for=...>` pointing at it,
+/// e.g. "3 contributions on January 2nd.". Older pages carried the count in a
+/// `data-count` attribute instead, which is used when present.
+pub fn parse_contributions_page(html: &str) -> Result<ContributionData, FetchError> {
+    let tooltips: HashMap<&str, u32> = elements(html, "<tool-tip")
+        .filter_map(|(tag, text)| Some((attribute(tag, "for")?, parse_tooltip_count(text)?)))
+        .collect();
+
+    let mut daily_contributions = Vec::new();
+    for (tag, _) in
+        elements(html, "<td").filter(|(tag, _)| tag.contains("ContributionCalendar-day"))
+    {
+        let Some(date) = attribute(tag, "data-date") else {
+            continue;
+        };
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| FetchError::UnexpectedResponseFormat)?;
+        let count = match attribute(tag, "data-count") {
+            Some(count) => count.parse().ok(),
+            None => attribute(tag, "id").and_then(|id| tooltips.get(id).copied()),
+        }
+        .ok_or(FetchError::UnexpectedResponseFormat)?;
+        daily_contributions.push((date, count));
+    }
+
+    if daily_contributions.is_empty() {
+        return Err(FetchError::UnexpectedResponseFormat);
+    }
+
+    // The calendar is a table, so cells come row by row (one weekday at a time);
+    // summarize_contributions puts them back in date order
+    let total = daily_contributions.iter().map(|&(_, count)| count).sum();
+
+    let mut data = summarize_contributions(total, daily_contributions);
+    data.limited = true;
+    Ok(data)
+}
+
+/// Iterates over `(opening tag, inner text)` pairs of every element that
+/// starts with `open`.
+fn elements<'a>(html: &'a str, open: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+    html.match_indices(open).filter_map(move |(start, _)| {
+        let rest = &html[start..];
+        let tag_end = rest.find('>')?;
+        let tag = &rest[..tag_end];
+        let inner = &rest[tag_end + 1..];
+        let text = &inner[..inner.find('<').unwrap_or(inner.len())];
+        Some((tag, text))
+    })
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let needle = format!(" {}=\"", name);
+    let start = tag.find(&needle)? + needle.len();
+    let len = tag[start..].find('"')?;
+    Some(&tag[start..start + len])
+}
+
+fn parse_tooltip_count(text: &str) -> Option<u32> {
+    let first_word = text.split_whitespace().next()?;
+    if first_word == "No" {
+        Some(0)
+    } else {
+        first_word.replace(',', "").parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/contributions_2024.html");
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn parses_days_in_chronological_order() {
+        let data = parse_contributions_page(FIXTURE).unwrap();
+        assert_eq!(data.daily_contributions.len(), 20);
+        assert_eq!(data.daily_contributions.first(), Some(&(date(1), 0)));
+        assert_eq!(data.daily_contributions[8], (date(9), 12));
+        assert_eq!(data.daily_contributions.last(), Some(&(date(20), 3)));
+        assert!(data
+            .daily_contributions
+            .windows(2)
+            .all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn summarizes_counts_and_marks_data_as_limited() {
+        let data = parse_contributions_page(FIXTURE).unwrap();
+        assert_eq!(data.total, 42);
+        assert_eq!(data.max_contributions, 12);
+        assert_eq!(data.longest_streak, 4);
+        assert!(data.limited);
+    }
+
+    #[test]
+    fn reads_legacy_data_count_attribute() {
+        let html = r#"<td class="ContributionCalendar-day" data-count="2" data-date="2020-02-04"></td>
+            <td class="ContributionCalendar-day" data-count="5" data-date="2020-02-03"></td>"#;
+        let data = parse_contributions_page(html).unwrap();
+        assert_eq!(
+            data.daily_contributions,
+            vec![
+                (NaiveDate::from_ymd_opt(2020, 2, 3).unwrap(), 5),
+                (NaiveDate::from_ymd_opt(2020, 2, 4).unwrap(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_pages_without_a_calendar() {
+        assert!(matches!(
+            parse_contributions_page("<html><body>Not Found</body></html>"),
+            Err(FetchError::UnexpectedResponseFormat)
+        ));
+    }
+}
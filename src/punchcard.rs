@@ -0,0 +1,77 @@
+use crate::timezone::Zone;
+use chrono::{DateTime, Datelike, FixedOffset, Timelike, Weekday};
+
+/// Commit counts by weekday (Sunday first, like the calendar) and hour.
+pub type Punchcard = [[u32; 24]; 7];
+
+/// Buckets commit timestamps into weekday/hour cells in the given time zone,
+/// regardless of the offset each commit was recorded with.
+pub fn bucket(times: &[DateTime<FixedOffset>], zone: Zone) -> Punchcard {
+    let mut punchcard = [[0; 24]; 7];
+    for time in times {
+        let local = zone.localize(time);
+        let day = local.weekday().num_days_from_sunday() as usize;
+        punchcard[day][local.hour() as usize] += 1;
+    }
+    punchcard
+}
+
+/// The weekday and hour with the most commits, if there are any.
+pub fn busiest_slot(punchcard: &Punchcard) -> Option<(Weekday, u32, u32)> {
+    let (day, hour, count) = punchcard
+        .iter()
+        .enumerate()
+        .flat_map(|(day, hours)| {
+            hours
+                .iter()
+                .enumerate()
+                .map(move |(hour, &count)| (day, hour, count))
+        })
+        .max_by_key(|&(day, hour, count)| (count, std::cmp::Reverse((day, hour))))?;
+    if count == 0 {
+        return None;
+    }
+    let weekday = [
+        Weekday::Sun,
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+    ][day];
+    Some((weekday, hour as u32, count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(time).unwrap()
+    }
+
+    #[test]
+    fn commits_are_bucketed_in_the_chosen_zone() {
+        // Monday morning in Tokyo is still Sunday evening in UTC
+        let utc = Zone::Fixed(FixedOffset::east_opt(0).unwrap());
+        let punchcard = bucket(&[at("2024-01-01T05:30:00+09:00")], utc);
+        assert_eq!(punchcard[0][20], 1);
+        assert_eq!(busiest_slot(&punchcard), Some((Weekday::Sun, 20, 1)));
+
+        let tokyo = Zone::parse("Asia/Tokyo").unwrap();
+        let punchcard = bucket(&[at("2023-12-31T20:30:00Z")], tokyo);
+        assert_eq!(busiest_slot(&punchcard), Some((Weekday::Mon, 5, 1)));
+    }
+
+    #[test]
+    fn busiest_slot_prefers_the_earliest_of_equal_cells() {
+        assert_eq!(busiest_slot(&[[0; 24]; 7]), None);
+
+        let mut punchcard = [[0; 24]; 7];
+        punchcard[3][9] = 2;
+        punchcard[1][17] = 2;
+        punchcard[1][8] = 1;
+        assert_eq!(busiest_slot(&punchcard), Some((Weekday::Mon, 17, 2)));
+    }
+}
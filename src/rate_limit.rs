@@ -0,0 +1,226 @@
+use crate::errors::FetchError;
+use crate::timezone;
+use chrono::{DateTime, Utc};
+use http::{HeaderMap, Response};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+const MAX_RETRIES: u32 = 4;
+const BASE_DELAY: Duration = Duration::from_secs(1);
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Enables reporting of retries and remaining API quota on stderr.
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Quota information from GitHub's `x-ratelimit-*` response headers.
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    pub reset_at: Option<DateTime<Utc>>,
+}
+
+impl RateLimit {
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let number = |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.parse().ok() };
+        Some(RateLimit {
+            limit: number("x-ratelimit-limit")?,
+            remaining: number("x-ratelimit-remaining")?,
+            reset_at: number("x-ratelimit-reset")
+                .and_then(|secs| DateTime::<Utc>::from_timestamp(secs as i64, 0)),
+        })
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Prints the remaining quota when `--verbose` is set. `cost` is the
+    /// point cost reported by the GraphQL `rateLimit` object, if queried.
+    pub fn report(&self, cost: Option<u64>) {
+        if !is_verbose() {
+            return;
+        }
+        let reset = self
+            .reset_at
+            .map(|reset_at| {
+                format!(
+                    ", resets at {}",
+                    timezone::zone().localize(&reset_at).format("%H:%M:%S")
+                )
+            })
+            .unwrap_or_default();
+        let cost = cost
+            .map(|cost| format!(" (query cost {})", cost))
+            .unwrap_or_default();
+        eprintln!(
+            "Rate limit: {}/{} remaining{}{}",
+            self.remaining, self.limit, reset, cost
+        );
+    }
+}
+
+/// Sends a request, retrying server errors, secondary rate limits and network
+/// failures with exponential backoff and jitter. Exhausting the primary rate
+/// limit is not retried, since the reset can be up to an hour away.
+pub async fn send_with_retry<B, F, Fut>(mut send: F) -> Result<Response<B>, FetchError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = octocrab::Result<Response<B>>>,
+{
+    let mut attempt = 0;
+    loop {
+        let result = send().await;
+        let retry = match &result {
+            Ok(response) => retry_delay(response, attempt)
+                .map(|delay| (delay, format!("HTTP {}", response.status().as_u16()))),
+            Err(octocrab::Error::Hyper { source, .. }) => {
+                Some((backoff(attempt), source.to_string()))
+            }
+            Err(octocrab::Error::Service { source, .. }) => {
+                Some((backoff(attempt), source.to_string()))
+            }
+            Err(_) => None,
+        };
+
+        match retry {
+            Some((delay, reason)) if attempt < MAX_RETRIES => {
+                attempt += 1;
+                if is_verbose() {
+                    eprintln!(
+                        "Request failed ({}), retrying in {:.1}s (attempt {}/{})",
+                        reason,
+                        delay.as_secs_f64(),
+                        attempt,
+                        MAX_RETRIES
+                    );
+                }
+                tokio::time::sleep(delay).await;
+            }
+            _ => return Ok(result?),
+        }
+    }
+}
+
+fn retry_delay<B>(response: &Response<B>, attempt: u32) -> Option<Duration> {
+    let status = response.status();
+    let headers = response.headers();
+    let primary_exhausted =
+        RateLimit::from_headers(headers).is_some_and(|limit| limit.is_exhausted());
+    let retry_after = headers
+        .get("retry-after")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .map(Duration::from_secs);
+
+    match status.as_u16() {
+        _ if status.is_server_error() => Some(backoff(attempt)),
+        // Secondary rate limits come back as 403 or 429 with quota left
+        403 | 429 if primary_exhausted => None,
+        429 => Some(retry_after.unwrap_or_else(|| backoff(attempt))),
+        403 => retry_after,
+        _ => None,
+    }
+    .map(|delay| delay.min(MAX_DELAY))
+}
+
+/// Exponential backoff with jitter, picking a delay between half and all of
+/// `BASE_DELAY * 2^attempt` so concurrent clients don't retry in lockstep.
+fn backoff(attempt: u32) -> Duration {
+    let ceiling = BASE_DELAY
+        .saturating_mul(1 << attempt.min(16))
+        .min(MAX_DELAY);
+    let jitter = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+    ceiling.mul_f64(0.5 + jitter / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, headers: &[(&str, &str)]) -> Response<()> {
+        let mut builder = Response::builder().status(status);
+        for &(name, value) in headers {
+            builder = builder.header(name, value);
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn rate_limit_is_read_from_headers() {
+        let headers = response(
+            200,
+            &[
+                ("x-ratelimit-limit", "5000"),
+                ("x-ratelimit-remaining", "0"),
+                ("x-ratelimit-reset", "1700000000"),
+            ],
+        )
+        .headers()
+        .clone();
+        let limit = RateLimit::from_headers(&headers).unwrap();
+        assert_eq!((limit.limit, limit.remaining), (5000, 0));
+        assert_eq!(limit.reset_at.unwrap().timestamp(), 1_700_000_000);
+        assert!(limit.is_exhausted());
+
+        let missing = response(200, &[("x-ratelimit-limit", "5000")]);
+        assert!(RateLimit::from_headers(missing.headers()).is_none());
+    }
+
+    #[test]
+    fn retry_after_sets_the_delay_of_secondary_rate_limits() {
+        let quota_left = [
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "42"),
+        ];
+        let forbidden = response(403, &[quota_left[0], quota_left[1], ("retry-after", "7")]);
+        assert_eq!(retry_delay(&forbidden, 0), Some(Duration::from_secs(7)));
+        let too_many = response(429, &[("retry-after", "600")]);
+        assert_eq!(retry_delay(&too_many, 0), Some(MAX_DELAY));
+        // A plain 403 is a permission error, not a rate limit
+        assert_eq!(retry_delay(&response(403, &quota_left), 0), None);
+    }
+
+    #[test]
+    fn exhausted_primary_rate_limit_is_not_retried() {
+        let exhausted = response(
+            429,
+            &[
+                ("x-ratelimit-limit", "5000"),
+                ("x-ratelimit-remaining", "0"),
+                ("x-ratelimit-reset", "1700000000"),
+                ("retry-after", "1"),
+            ],
+        );
+        assert_eq!(retry_delay(&exhausted, 0), None);
+    }
+
+    #[test]
+    fn server_errors_back_off_and_other_statuses_are_not_retried() {
+        let delay = retry_delay(&response(502, &[]), 2).unwrap();
+        assert!(delay >= BASE_DELAY * 2 && delay <= BASE_DELAY * 4);
+        assert_eq!(retry_delay(&response(404, &[]), 0), None);
+        assert_eq!(retry_delay(&response(200, &[]), 0), None);
+    }
+
+    #[test]
+    fn backoff_stays_between_half_and_all_of_the_ceiling() {
+        for attempt in 0..8 {
+            let ceiling = (BASE_DELAY * 2u32.pow(attempt)).min(MAX_DELAY);
+            let delay = backoff(attempt);
+            assert!(delay >= ceiling / 2 && delay <= ceiling, "{:?}", delay);
+        }
+        assert!(backoff(u32::MAX) <= MAX_DELAY);
+    }
+}
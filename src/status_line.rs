@@ -0,0 +1,181 @@
+use crate::config_manager::LineConfig;
+use crate::contribution_analyzer::{calculate_extended_stats, ContributionData};
+use crate::timezone;
+use chrono::{Duration, NaiveDate};
+use std::collections::HashMap;
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Fills the template's placeholders from `data`. Unknown placeholders are
+/// left as they are.
+pub fn render_line(
+    username: &str,
+    year: &str,
+    data: &ContributionData,
+    line_config: &LineConfig,
+) -> String {
+    render_line_at(username, year, data, line_config, timezone::today())
+}
+
+fn render_line_at(
+    username: &str,
+    year: &str,
+    data: &ContributionData,
+    line_config: &LineConfig,
+    today: NaiveDate,
+) -> String {
+    // A past year ends before today; count back from its last day instead
+    let until = data.date_range().map_or(today, |(_, last)| last.min(today));
+    let today_count = recent_counts(data, today, 1)[0];
+    let week: u32 = recent_counts(data, until, 7).iter().sum();
+    let stats = calculate_extended_stats(&data.daily_contributions);
+
+    let placeholders = [
+        ("username", username.to_string()),
+        ("year", year.to_string()),
+        ("total", data.total.to_string()),
+        ("longest_streak", data.longest_streak.to_string()),
+        ("current_streak", data.current_streak.to_string()),
+        ("max_contributions", data.max_contributions.to_string()),
+        ("active_days", stats.active_days.to_string()),
+        ("today", today_count.to_string()),
+        ("week", week.to_string()),
+        (
+            "sparkline",
+            sparkline(&recent_counts(data, until, line_config.sparkline_days)),
+        ),
+    ];
+    let mut line = line_config.template.clone();
+    for (name, value) in placeholders {
+        line = line.replace(&format!("{{{}}}", name), &value);
+    }
+    line
+}
+
+/// Waybar's custom module JSON: the line as `text`, the info panel as
+/// `tooltip`, and a `class` to style by: `active` once something was
+/// contributed today, `pending` while a streak still waits for today's
+/// contribution, and `inactive` otherwise.
+pub fn render_waybar(text: &str, tooltip: &[String], data: &ContributionData) -> String {
+    serde_json::json!({
+        "text": text,
+        "tooltip": tooltip.join("\n"),
+        "class": waybar_class(data, timezone::today())
+    })
+    .to_string()
+}
+
+fn waybar_class(data: &ContributionData, today: NaiveDate) -> &'static str {
+    if recent_counts(data, today, 1)[0] > 0 {
+        "active"
+    } else if data.current_streak > 0 {
+        "pending"
+    } else {
+        "inactive"
+    }
+}
+
+/// One bar per count, scaled to the largest.
+pub fn sparkline(counts: &[u32]) -> String {
+    let max = counts.iter().copied().max().unwrap_or(0);
+    counts
+        .iter()
+        .map(|&count| match max {
+            0 => SPARKS[0],
+            max => SPARKS[(count * 7).div_ceil(max) as usize],
+        })
+        .collect()
+}
+
+/// Counts of the `days` days up to and including `until`, oldest first.
+fn recent_counts(data: &ContributionData, until: NaiveDate, days: usize) -> Vec<u32> {
+    let counts: HashMap<NaiveDate, u32> = data.daily_contributions.iter().copied().collect();
+    (0..days as i64)
+        .rev()
+        .map(|offset| {
+            counts
+                .get(&(until - Duration::days(offset)))
+                .copied()
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contribution_analyzer::summarize_contributions;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, month, day).unwrap()
+    }
+
+    /// The last `counts.len()` days of 2023, with the given counts.
+    fn year_end(counts: &[u32]) -> ContributionData {
+        let first = date(12, 31) - Duration::days(counts.len() as i64 - 1);
+        let daily = first.iter_days().zip(counts.iter().copied()).collect();
+        summarize_contributions(counts.iter().sum(), daily)
+    }
+
+    fn line(template: &str, data: &ContributionData, today: NaiveDate) -> String {
+        let line_config = LineConfig {
+            template: template.to_string(),
+            sparkline_days: 3,
+        };
+        render_line_at("octocat", "2023", data, &line_config, today)
+    }
+
+    #[test]
+    fn placeholders_are_filled_and_unknown_ones_kept() {
+        let data = year_end(&[1, 0, 2, 3]);
+        assert_eq!(
+            line(
+                "{username}@{year}: {total} total, {max_contributions} max, {nope} {today}",
+                &data,
+                date(12, 31)
+            ),
+            "octocat@2023: 6 total, 3 max, {nope} 3"
+        );
+        assert_eq!(
+            line("{current_streak}d/{longest_streak}d", &data, date(12, 31)),
+            "2d/2d"
+        );
+    }
+
+    #[test]
+    fn past_years_count_back_from_their_last_day() {
+        let data = year_end(&[5, 1, 0, 2, 3, 1, 1, 1]);
+        let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        // The week and sparkline end on December 31st, not today
+        assert_eq!(line("{week} {sparkline} {today}", &data, today), "9 ███ 0");
+        // A range running past today ends today
+        assert_eq!(line("{week} {sparkline}", &data, date(12, 29)), "12 ▆█▄");
+    }
+
+    #[test]
+    fn sparklines_scale_to_the_largest_count() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[0, 0, 0]), "▁▁▁");
+        assert_eq!(sparkline(&[0, 10, 0, 1]), "▁█▁▂");
+        assert_eq!(sparkline(&[2, 4, 6, 8]), "▃▅▇█");
+    }
+
+    #[test]
+    fn waybar_class_follows_today_and_the_streak() {
+        let data = year_end(&[1, 1, 2]);
+        assert_eq!(waybar_class(&data, date(12, 31)), "active");
+        // Nothing yet on the day after, but the streak is still alive
+        let pending = year_end(&[1, 1, 2, 0]);
+        assert_eq!(pending.current_streak, 3);
+        assert_eq!(waybar_class(&pending, date(12, 31)), "pending");
+        assert_eq!(
+            waybar_class(&year_end(&[1, 0, 0]), date(12, 31)),
+            "inactive"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_waybar("3d", &["a".into(), "b".into()], &data)).unwrap();
+        assert_eq!(json["text"], "3d");
+        assert_eq!(json["tooltip"], "a\nb");
+    }
+}
@@ -0,0 +1,187 @@
+use crate::contribution_analyzer::{summarize_contributions, ContributionData};
+use crate::contribution_source::ContributionSource;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Caps in-flight requests so large teams don't trip secondary rate limits.
+const MAX_CONCURRENT_FETCHES: usize = 8;
+
+pub struct TeamEntry {
+    pub username: String,
+    pub data: ContributionData,
+}
+
+/// Fetches every member's contributions concurrently, returning the results
+/// in the order of `members`. A fetch that panics fails like any other.
+pub async fn fetch_team(
+    source: &ContributionSource,
+    members: &[String],
+    year: &str,
+    year_specified: bool,
+) -> Vec<(String, anyhow::Result<ContributionData>)> {
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_FETCHES));
+    let tasks: Vec<_> = members
+        .iter()
+        .map(|username| {
+            let source = source.clone();
+            let semaphore = Arc::clone(&semaphore);
+            let (member, year) = (username.clone(), year.to_string());
+            let task = tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                source.fetch(&member, &year, year_specified).await
+            });
+            (username.clone(), task)
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(members.len());
+    for (username, task) in tasks {
+        let result = match task.await {
+            Ok(result) => result.map_err(anyhow::Error::from),
+            Err(e) => Err(anyhow::anyhow!("the fetch failed unexpectedly: {}", e)),
+        };
+        results.push((username, result));
+    }
+    results
+}
+
+/// Sorts by total contributions, breaking ties by longest streak.
+pub fn rank(entries: &mut [TeamEntry]) {
+    entries.sort_by(|a, b| {
+        b.data
+            .total
+            .cmp(&a.data.total)
+            .then(b.data.longest_streak.cmp(&a.data.longest_streak))
+            .then(a.username.cmp(&b.username))
+    });
+}
+
+pub fn busiest_day(daily_contributions: &[(NaiveDate, u32)]) -> Option<(NaiveDate, u32)> {
+    daily_contributions
+        .iter()
+        .filter(|&&(_, count)| count > 0)
+        .max_by_key(|&&(date, count)| (count, std::cmp::Reverse(date)))
+        .copied()
+}
+
+/// Sums the members' daily counts into one calendar for the team heatmap.
+pub fn combine(entries: &[TeamEntry]) -> ContributionData {
+    let mut combined: BTreeMap<NaiveDate, u32> = BTreeMap::new();
+    for entry in entries {
+        for &(date, count) in &entry.data.daily_contributions {
+            *combined.entry(date).or_default() += count;
+        }
+    }
+    let total = entries.iter().map(|entry| entry.data.total).sum();
+    let mut data = summarize_contributions(total, combined.into_iter().collect());
+    data.limited = entries.iter().any(|entry| entry.data.limited);
+    data
+}
+
+pub fn render_leaderboard(entries: &[TeamEntry]) -> String {
+    let name_width = entries
+        .iter()
+        .map(|entry| entry.username.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("User".len());
+
+    let mut table = format!(
+        "{:>3}  {:<name_width$}  {:>6}  {:>7}  {:>7}  {}\n",
+        "#", "User", "Total", "Longest", "Current", "Busiest day"
+    );
+    for (rank, entry) in entries.iter().enumerate() {
+        let busiest = busiest_day(&entry.data.daily_contributions)
+            .map(|(date, count)| format!("{} ({})", date.format("%Y-%m-%d"), count))
+            .unwrap_or_else(|| "-".to_string());
+        table += &format!(
+            "{:>3}  {:<name_width$}  {:>6}  {:>7}  {:>7}  {}\n",
+            rank + 1,
+            entry.username,
+            entry.data.total,
+            entry.data.longest_streak,
+            entry.data.current_streak,
+            busiest
+        );
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn entry(username: &str, counts: &[(u32, u32)]) -> TeamEntry {
+        let daily = counts
+            .iter()
+            .map(|&(day, count)| (date(day), count))
+            .collect();
+        let total = counts.iter().map(|&(_, count)| count).sum();
+        TeamEntry {
+            username: username.to_string(),
+            data: summarize_contributions(total, daily),
+        }
+    }
+
+    #[test]
+    fn ties_are_broken_by_longest_streak_then_name() {
+        let mut entries = vec![
+            entry("dave", &[(1, 1), (3, 1)]),
+            entry("carol", &[(1, 2), (2, 1)]),
+            entry("bob", &[(1, 1), (2, 1)]),
+            entry("alice", &[(1, 1), (3, 1)]),
+        ];
+        rank(&mut entries);
+        let order: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry.username.as_str())
+            .collect();
+        assert_eq!(order, ["carol", "bob", "alice", "dave"]);
+    }
+
+    #[test]
+    fn combined_calendars_sum_counts_on_shared_dates() {
+        let entries = [
+            entry("alice", &[(1, 1), (2, 3)]),
+            entry("bob", &[(2, 2), (3, 4)]),
+        ];
+        let combined = combine(&entries);
+        assert_eq!(
+            combined.daily_contributions,
+            [(date(1), 1), (date(2), 5), (date(3), 4)]
+        );
+        assert_eq!(combined.total, 10);
+        assert_eq!(combined.longest_streak, 3);
+        assert!(!combined.limited);
+    }
+
+    #[test]
+    fn busiest_days_prefer_the_earliest_of_equal_counts() {
+        assert_eq!(
+            busiest_day(&[(date(1), 2), (date(2), 3), (date(3), 3)]),
+            Some((date(2), 3))
+        );
+        assert_eq!(busiest_day(&[(date(1), 0)]), None);
+    }
+
+    #[test]
+    fn leaderboard_lists_members_in_rank_order() {
+        let entries = [entry("alice", &[(1, 3), (2, 1)]), entry("bo", &[])];
+        let table = render_leaderboard(&entries);
+        let rows: Vec<&str> = table.lines().collect();
+        assert_eq!(
+            rows,
+            [
+                "  #  User    Total  Longest  Current  Busiest day",
+                "  1  alice       4        2        2  2024-01-01 (3)",
+                "  2  bo          0        0        0  -",
+            ]
+        );
+    }
+}
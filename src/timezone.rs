@@ -0,0 +1,134 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::fmt;
+use std::sync::OnceLock;
+
+static ZONE: OnceLock<Zone> = OnceLock::new();
+
+/// The time zone days are counted in: the system's unless `--tz` or the
+/// `timezone` config option names another.
+#[derive(Clone, Copy, Debug)]
+pub enum Zone {
+    Local,
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+impl Zone {
+    /// Accepts IANA names like "Europe/Berlin" and offsets like "+02:00".
+    pub fn parse(name: &str) -> Option<Zone> {
+        if name.eq_ignore_ascii_case("local") {
+            return Some(Zone::Local);
+        }
+        name.parse::<Tz>()
+            .map(Zone::Named)
+            .or_else(|_| name.parse::<FixedOffset>().map(Zone::Fixed))
+            .ok()
+    }
+
+    /// Wall-clock time in this zone at the given instant.
+    pub fn localize<T: TimeZone>(&self, time: &DateTime<T>) -> NaiveDateTime {
+        match self {
+            Zone::Local => time.with_timezone(&Local).naive_local(),
+            Zone::Named(tz) => time.with_timezone(tz).naive_local(),
+            Zone::Fixed(offset) => time.with_timezone(offset).naive_local(),
+        }
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.localize(&Utc::now()).date()
+    }
+
+    /// The instant `date` begins in this zone. Days that start inside a DST
+    /// gap begin at the first valid time.
+    pub fn start_of_day(&self, date: NaiveDate) -> DateTime<FixedOffset> {
+        match self {
+            Zone::Local => first_instant(&Local, date),
+            Zone::Named(tz) => first_instant(tz, date),
+            Zone::Fixed(offset) => first_instant(offset, date),
+        }
+    }
+}
+
+fn first_instant<T: TimeZone>(tz: &T, date: NaiveDate) -> DateTime<FixedOffset> {
+    (0..3)
+        .filter_map(|hour| date.and_hms_opt(hour, 0, 0))
+        .find_map(|time| tz.from_local_datetime(&time).earliest())
+        .map(|time| time.fixed_offset())
+        .unwrap_or_else(|| date.and_time(NaiveTime::MIN).and_utc().fixed_offset())
+}
+
+impl fmt::Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Zone::Local => write!(f, "local (UTC{})", Local::now().offset()),
+            Zone::Named(tz) => write!(f, "{}", tz.name()),
+            Zone::Fixed(offset) => write!(f, "UTC{}", offset),
+        }
+    }
+}
+
+/// Sets the zone for the rest of the run; only the first call has an effect.
+pub fn set_zone(zone: Zone) {
+    let _ = ZONE.set(zone);
+}
+
+pub fn zone() -> Zone {
+    ZONE.get().copied().unwrap_or(Zone::Local)
+}
+
+/// Today's date in the configured zone.
+pub fn today() -> NaiveDate {
+    zone().today()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn zones_parse_from_names_offsets_and_local() {
+        assert!(
+            matches!(Zone::parse("Europe/Berlin"), Some(Zone::Named(tz)) if tz == Tz::Europe__Berlin)
+        );
+        assert!(matches!(
+            Zone::parse("+02:00"),
+            Some(Zone::Fixed(offset)) if offset.local_minus_utc() == 2 * 3600
+        ));
+        assert!(matches!(
+            Zone::parse("-05:30"),
+            Some(Zone::Fixed(offset)) if offset.local_minus_utc() == -(5 * 3600 + 1800)
+        ));
+        assert!(matches!(Zone::parse("local"), Some(Zone::Local)));
+        assert!(matches!(Zone::parse("Local"), Some(Zone::Local)));
+        for garbage in ["", "Mars/Base", "+25:00", "tomorrow"] {
+            assert!(Zone::parse(garbage).is_none(), "{:?} parsed", garbage);
+        }
+    }
+
+    #[test]
+    fn days_start_at_midnight_or_after_a_dst_gap() {
+        let start = |zone: &str, day| Zone::parse(zone).unwrap().start_of_day(day).to_rfc3339();
+        assert_eq!(
+            start("Europe/Berlin", date(2024, 3, 14)),
+            "2024-03-14T00:00:00+01:00"
+        );
+        assert_eq!(
+            start("+02:00", date(2024, 3, 14)),
+            "2024-03-14T00:00:00+02:00"
+        );
+        // Chile springs forward at midnight, so September 8th, 2024 starts at 1am
+        assert_eq!(
+            start("America/Santiago", date(2024, 9, 8)),
+            "2024-09-08T01:00:00-03:00"
+        );
+        assert_eq!(
+            start("America/Santiago", date(2024, 9, 9)),
+            "2024-09-09T00:00:00-03:00"
+        );
+    }
+}
@@ -0,0 +1,443 @@
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config_manager::{self, Config, DEFAULT_HOST};
+
+const KEYRING_SERVICE: &str = "gitfetch";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenSource {
+    Environment(&'static str),
+    TokenCommand,
+    Keyring,
+    GhHostsFile,
+    ConfigFile,
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenSource::Environment(var) => write!(f, "{} environment variable", var),
+            TokenSource::TokenCommand => write!(f, "token_command"),
+            TokenSource::Keyring => write!(f, "system keyring"),
+            TokenSource::GhHostsFile => write!(f, "gh CLI hosts file"),
+            TokenSource::ConfigFile => write!(f, "config file (plaintext)"),
+        }
+    }
+}
+
+pub struct ResolvedToken {
+    pub value: String,
+    pub source: TokenSource,
+}
+
+/// Looks up the token for `host`, in order of precedence: the environment
+/// (`GH_TOKEN`/`GITHUB_TOKEN` for github.com, `GH_ENTERPRISE_TOKEN`/
+/// `GITHUB_ENTERPRISE_TOKEN` for other hosts), the configured `token_command`,
+/// the system keyring, the plaintext token in the config file and finally the
+/// gh CLI hosts file. Tokens stored with `gitfetch token add` rank above the
+/// one gh happens to have.
+pub fn resolve_token(config: &Config, host: &str) -> Result<Option<ResolvedToken>> {
+    resolve_token_from(
+        config,
+        host,
+        |var| std::env::var(var).ok(),
+        keyring_lookup,
+        gh_hosts_token,
+    )
+}
+
+/// `resolve_token` with the environment, keyring and gh CLI lookups passed
+/// in. The keyring and gh are only asked when nothing before them has a token.
+fn resolve_token_from(
+    config: &Config,
+    host: &str,
+    env: impl Fn(&str) -> Option<String>,
+    keyring: impl FnOnce(&str) -> Option<String>,
+    gh_hosts: impl FnOnce(&str) -> Option<String>,
+) -> Result<Option<ResolvedToken>> {
+    let env_vars = if host == DEFAULT_HOST {
+        ["GH_TOKEN", "GITHUB_TOKEN"]
+    } else {
+        ["GH_ENTERPRISE_TOKEN", "GITHUB_ENTERPRISE_TOKEN"]
+    };
+    for var in env_vars {
+        if let Some(value) = non_empty(env(var)) {
+            return Ok(Some(ResolvedToken {
+                value,
+                source: TokenSource::Environment(var),
+            }));
+        }
+    }
+
+    if let Some(command) = config.token_command(host) {
+        let value = run_token_command(command)?;
+        return Ok(Some(ResolvedToken {
+            value,
+            source: TokenSource::TokenCommand,
+        }));
+    }
+
+    let found = |value: Option<String>, source| {
+        non_empty(value).map(|value| ResolvedToken { value, source })
+    };
+    Ok(found(keyring(host), TokenSource::Keyring)
+        .or_else(|| {
+            found(
+                config.plaintext_token(host).map(str::to_string),
+                TokenSource::ConfigFile,
+            )
+        })
+        .or_else(|| found(gh_hosts(host), TokenSource::GhHostsFile)))
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn run_token_command(command: &str) -> Result<String> {
+    let output = Command::new("sh")
+        .args(["-c", command])
+        .stderr(Stdio::inherit())
+        .output()
+        .with_context(|| format!("Failed to run token_command: {}", command))?;
+    if !output.status.success() {
+        anyhow::bail!("token_command `{}` exited with {}", command, output.status);
+    }
+    // Tools like `pass` print the secret on the first line, followed by metadata
+    let stdout = String::from_utf8(output.stdout).context("token_command output is not UTF-8")?;
+    non_empty(stdout.lines().next().map(str::to_string))
+        .with_context(|| format!("token_command `{}` printed no token", command))
+}
+
+/// Reads the `oauth_token` of `host` from the gh CLI's `hosts.yml`.
+fn gh_hosts_token(host: &str) -> Option<String> {
+    parse_gh_hosts(&fs::read_to_string(gh_hosts_path()?).ok()?, host)
+}
+
+/// Finds the `oauth_token` of `host` in the contents of a `hosts.yml`. Only
+/// the small subset of YAML that gh writes is understood.
+fn parse_gh_hosts(contents: &str, host: &str) -> Option<String> {
+    let mut in_host = false;
+    for line in contents.lines() {
+        if !line.starts_with(' ') && !line.starts_with('\t') {
+            in_host = line.trim_end().trim_end_matches(':') == host;
+        } else if in_host {
+            if let Some(token) = line.trim().strip_prefix("oauth_token:") {
+                return Some(
+                    token
+                        .trim()
+                        .trim_matches('"')
+                        .trim_matches('\'')
+                        .to_string(),
+                );
+            }
+        }
+    }
+    None
+}
+
+fn gh_hosts_path() -> Option<PathBuf> {
+    let dir = match std::env::var("GH_CONFIG_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => match std::env::var("XDG_CONFIG_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir).join("gh"),
+            _ => PathBuf::from(std::env::var("HOME").ok()?)
+                .join(".config")
+                .join("gh"),
+        },
+    };
+    Some(dir.join("hosts.yml"))
+}
+
+/// Where `store_token` put the token.
+pub enum StoredIn {
+    Keyring,
+    /// The plaintext config file, with the reason the keyring couldn't be
+    /// used when it was tried.
+    ConfigFile(Option<anyhow::Error>),
+}
+
+/// Stores the token in the system keyring, falling back to the plaintext
+/// config file when no keyring is available or `plaintext` is requested.
+pub fn store_token(
+    token: &str,
+    host: &str,
+    plaintext: bool,
+    config_path: Option<&Path>,
+) -> Result<StoredIn> {
+    let keyring_error = if plaintext {
+        None
+    } else {
+        match keyring_store(host, token) {
+            Ok(()) => return Ok(StoredIn::Keyring),
+            Err(e) => Some(e),
+        }
+    };
+    config_manager::add_token(token, host, config_path)?;
+    Ok(StoredIn::ConfigFile(keyring_error))
+}
+
+/// What `remove_token` found to remove.
+pub struct Removed {
+    pub from_keyring: bool,
+    pub from_config: bool,
+    /// Where a token still comes from, like the environment, if anywhere.
+    pub remaining: Option<TokenSource>,
+}
+
+/// Removes the stored token for `host` from the keyring and the config file.
+/// Tokens from the environment, `token_command` or gh are left alone, but
+/// reported so the user knows one is still in effect.
+pub fn remove_token(config: &Config, host: &str, config_path: Option<&Path>) -> Result<Removed> {
+    let from_keyring = keyring_delete(host);
+    let from_config = config_manager::remove_token(host, config_path)?;
+    let remaining = resolve_token(config, host)?
+        .map(|token| token.source)
+        .filter(|source| !matches!(source, TokenSource::Keyring | TokenSource::ConfigFile));
+    Ok(Removed {
+        from_keyring,
+        from_config,
+        remaining,
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn keyring_lookup(host: &str) -> Option<String> {
+    let output = Command::new("security")
+        .args([
+            "find-generic-password",
+            "-s",
+            KEYRING_SERVICE,
+            "-a",
+            host,
+            "-w",
+        ])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8(output.stdout).ok())
+        .flatten()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn keyring_lookup(host: &str) -> Option<String> {
+    let output = Command::new("secret-tool")
+        .args(["lookup", "service", KEYRING_SERVICE, "host", host])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8(output.stdout).ok())
+        .flatten()
+}
+
+/// Stores the token in the macOS Keychain.
+#[cfg(target_os = "macos")]
+fn keyring_store(host: &str, token: &str) -> Result<()> {
+    // `-w` without a value, last, makes `security` prompt for the password
+    // and read it from stdin, so it never shows up in `ps`
+    let mut child = Command::new("security")
+        .args([
+            "add-generic-password",
+            "-U",
+            "-s",
+            KEYRING_SERVICE,
+            "-a",
+            host,
+            "-w",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to run `security`")?;
+    // Once for the prompt and once for the confirmation
+    child
+        .stdin
+        .take()
+        .context("Failed to open security stdin")?
+        .write_all(format!("{0}\n{0}\n", token).as_bytes())?;
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("`security add-generic-password` exited with {}", status);
+    }
+    Ok(())
+}
+
+/// Stores the token in the Secret Service keyring through `secret-tool`.
+#[cfg(not(target_os = "macos"))]
+fn keyring_store(host: &str, token: &str) -> Result<()> {
+    let label = format!("gitfetch token for {}", host);
+    let mut child = Command::new("secret-tool")
+        .args([
+            "store",
+            "--label",
+            &label,
+            "service",
+            KEYRING_SERVICE,
+            "host",
+            host,
+        ])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to run `secret-tool` (is libsecret installed?)")?;
+    // secret-tool reads the secret from stdin so it never shows up in `ps`
+    child
+        .stdin
+        .take()
+        .context("Failed to open secret-tool stdin")?
+        .write_all(token.as_bytes())?;
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("`secret-tool store` exited with {}", status);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn keyring_delete(host: &str) -> bool {
+    Command::new("security")
+        .args(["delete-generic-password", "-s", KEYRING_SERVICE, "-a", host])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn keyring_delete(host: &str) -> bool {
+    // `secret-tool clear` succeeds even when nothing matched
+    if keyring_lookup(host).is_none() {
+        return false;
+    }
+    Command::new("secret-tool")
+        .args(["clear", "service", KEYRING_SERVICE, "host", host])
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_manager::HostConfig;
+    use std::collections::{BTreeMap, HashMap};
+
+    fn resolve(
+        config: &Config,
+        host: &str,
+        env: &[(&str, &str)],
+        keyring: Option<&str>,
+        gh_hosts: Option<&str>,
+    ) -> Option<(String, TokenSource)> {
+        let env: HashMap<&str, &str> = env.iter().copied().collect();
+        resolve_token_from(
+            config,
+            host,
+            |var| env.get(var).map(|value| value.to_string()),
+            |_| keyring.map(str::to_string),
+            |_| gh_hosts.map(str::to_string),
+        )
+        .unwrap()
+        .map(|token| (token.value, token.source))
+    }
+
+    fn found(value: &str, source: TokenSource) -> Option<(String, TokenSource)> {
+        Some((value.to_string(), source))
+    }
+
+    #[test]
+    fn tokens_are_resolved_in_order_of_precedence() {
+        let mut config = Config {
+            github_token: Some("config".into()),
+            token_command: Some("echo command".into()),
+            ..Config::default()
+        };
+        let env = [("GITHUB_TOKEN", "github"), ("GH_TOKEN", "gh")];
+        let (keyring, gh) = (Some("keyring"), Some("hosts"));
+
+        let resolved = resolve(&config, DEFAULT_HOST, &env, keyring, gh);
+        assert_eq!(resolved, found("gh", TokenSource::Environment("GH_TOKEN")));
+        let resolved = resolve(&config, DEFAULT_HOST, &env[..1], keyring, gh);
+        assert_eq!(
+            resolved,
+            found("github", TokenSource::Environment("GITHUB_TOKEN"))
+        );
+        let resolved = resolve(&config, DEFAULT_HOST, &[("GH_TOKEN", " ")], keyring, gh);
+        assert_eq!(resolved, found("command", TokenSource::TokenCommand));
+
+        config.token_command = None;
+        let resolved = resolve(&config, DEFAULT_HOST, &[], keyring, gh);
+        assert_eq!(resolved, found("keyring", TokenSource::Keyring));
+        let resolved = resolve(&config, DEFAULT_HOST, &[], None, gh);
+        assert_eq!(resolved, found("config", TokenSource::ConfigFile));
+
+        config.github_token = None;
+        let resolved = resolve(&config, DEFAULT_HOST, &[], None, gh);
+        assert_eq!(resolved, found("hosts", TokenSource::GhHostsFile));
+        assert_eq!(resolve(&config, DEFAULT_HOST, &[], None, None), None);
+    }
+
+    #[test]
+    fn other_hosts_use_the_enterprise_variables_and_their_own_config() {
+        let config = Config {
+            github_token: Some("github.com".into()),
+            hosts: Some(BTreeMap::from([(
+                "ghe.example.com".to_string(),
+                HostConfig {
+                    api_url: None,
+                    token: Some("enterprise".into()),
+                    token_command: None,
+                },
+            )])),
+            ..Config::default()
+        };
+        let env = [("GH_TOKEN", "gh"), ("GITHUB_ENTERPRISE_TOKEN", "ghe")];
+        assert_eq!(
+            resolve(&config, "ghe.example.com", &env, None, None),
+            found("ghe", TokenSource::Environment("GITHUB_ENTERPRISE_TOKEN"))
+        );
+        assert_eq!(
+            resolve(&config, "ghe.example.com", &env[..1], None, None),
+            found("enterprise", TokenSource::ConfigFile)
+        );
+    }
+
+    #[test]
+    fn gh_hosts_token_is_read_from_the_host_section() {
+        let hosts = "\
+github.com:
+    user: octocat
+    oauth_token: gho_public
+    git_protocol: https
+ghe.example.com:
+\tgit_protocol: ssh
+\toauth_token: \"gho_enterprise\"
+";
+        assert_eq!(parse_gh_hosts(hosts, "github.com").unwrap(), "gho_public");
+        assert_eq!(
+            parse_gh_hosts(hosts, "ghe.example.com").unwrap(),
+            "gho_enterprise"
+        );
+        assert_eq!(parse_gh_hosts(hosts, "example.com"), None);
+        assert_eq!(
+            parse_gh_hosts("github.com:\n    user: octocat\n", "github.com"),
+            None
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use crate::color;
+use crate::config_manager::{Config, Orientation};
+use crate::contribution_analyzer::ContributionData;
+use crate::contribution_source::ContributionSource;
+use crate::day::render_day;
+use crate::github_client::fetch_day_activity;
+use crate::graph_generator::{
+    colorize_info, generate_contribution_graph, generate_highlighted_graph,
+};
+use crate::info::{contribution_info, shows_profile, InfoLine};
+use crate::profile::Profile;
+use crate::timezone;
+use anyhow::Result;
+use chrono::{Datelike, Duration, NaiveDate};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, Write};
+
+/// Width the stats panel needs to sit beside the graph rather than below it.
+const PANEL_WIDTH: usize = 36;
+
+const HELP: &str = "arrows/hjkl move  enter day details  [ ] year  u user  q quit";
+
+/// Puts the terminal back however the view is left, including on errors.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct App<'a> {
+    source: &'a ContributionSource,
+    config: &'a Config,
+    username: String,
+    /// A calendar year, or `None` for the past year up to today.
+    year: Option<i32>,
+    data: Option<ContributionData>,
+    /// Columns the graph of `data` takes up, measured once when it loads.
+    graph_width: usize,
+    /// Whose profile was last fetched, and the profile if there was one.
+    profile: Option<(String, Option<Profile>)>,
+    cursor: NaiveDate,
+    status: String,
+    /// The username being typed after pressing `u`.
+    input: Option<String>,
+    /// What was contributed on the cursor's day, shown in place of the stats
+    /// after pressing Enter.
+    detail: Option<String>,
+}
+
+/// Runs the full-screen view until the user quits.
+pub async fn run(
+    source: &ContributionSource,
+    config: &Config,
+    username: String,
+    year: Option<i32>,
+) -> Result<()> {
+    let _guard = TerminalGuard::enter()?;
+    let mut app = App {
+        source,
+        config,
+        username,
+        year,
+        data: None,
+        graph_width: 0,
+        profile: None,
+        cursor: timezone::today(),
+        status: String::new(),
+        input: None,
+        detail: None,
+    };
+    app.load().await?;
+
+    loop {
+        app.draw()?;
+        // Reading blocks, so keep it off the runtime's worker threads
+        let Event::Key(key) = tokio::task::spawn_blocking(event::read).await?? else {
+            // Resizes and the like just redraw
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            break;
+        }
+        if app.input.is_some() {
+            app.handle_input(key).await?;
+            continue;
+        }
+        // Any key closes the day details; Enter and Esc do nothing else
+        if app.detail.take().is_some() && matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Left | KeyCode::Char('h') => app.move_cursor(-1, 0),
+            KeyCode::Right | KeyCode::Char('l') => app.move_cursor(1, 0),
+            KeyCode::Up | KeyCode::Char('k') => app.move_cursor(0, -1),
+            KeyCode::Down | KeyCode::Char('j') => app.move_cursor(0, 1),
+            KeyCode::Char('[') => {
+                let this_year = timezone::today().year();
+                app.year = Some(app.year.map_or(this_year, |year| year - 1));
+                app.load().await?;
+            }
+            KeyCode::Char(']') => {
+                let this_year = timezone::today().year();
+                app.year = app
+                    .year
+                    .map(|year| year + 1)
+                    .filter(|&year| year <= this_year);
+                app.load().await?;
+            }
+            KeyCode::Enter => app.load_detail().await?,
+            KeyCode::Char('u') => app.input = Some(String::new()),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+impl App<'_> {
+    fn year_label(&self) -> String {
+        self.year
+            .unwrap_or_else(|| timezone::today().year())
+            .to_string()
+    }
+
+    /// Fetches the current user and year, keeping what was shown before if
+    /// that fails. Returns whether it succeeded.
+    async fn load(&mut self) -> Result<bool> {
+        self.status = format!("Loading {}@{}...", self.username, self.year_label());
+        self.draw()?;
+
+        match self
+            .source
+            .fetch(&self.username, &self.year_label(), self.year.is_some())
+            .await
+        {
+            Ok(data) => {
+                if let Some((first, last)) = data.date_range() {
+                    if !(first..=last).contains(&self.cursor) {
+                        self.cursor = last;
+                    }
+                }
+                self.graph_width = generate_contribution_graph(
+                    &data.daily_contributions,
+                    self.year.is_some(),
+                    self.config,
+                )
+                .lines()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap_or(0);
+                self.data = Some(data);
+                self.status = HELP.to_string();
+                self.load_profile().await;
+                Ok(true)
+            }
+            Err(e) => {
+                self.status = format!("Error: {}", e);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Fetches the profile when the panel shows it and the user changed. A
+    /// failure just leaves the profile fields out.
+    async fn load_profile(&mut self) {
+        if !shows_profile(self.config)
+            || matches!(&self.profile, Some((user, _)) if *user == self.username)
+        {
+            return;
+        }
+        let profile = self.source.profile(&self.username).await.ok().flatten();
+        self.profile = Some((self.username.clone(), profile));
+    }
+
+    async fn load_detail(&mut self) -> Result<()> {
+        let Some(octocrab) = self.source.octocrab() else {
+            self.status = "Day details need a GitHub token".to_string();
+            return Ok(());
+        };
+        self.status = format!("Loading {}...", self.cursor);
+        self.draw()?;
+
+        match fetch_day_activity(octocrab, &self.username, self.cursor).await {
+            Ok(activity) => {
+                self.detail = Some(render_day(self.cursor, &activity));
+                self.status = HELP.to_string();
+            }
+            Err(e) => self.status = format!("Error: {}", e),
+        }
+        Ok(())
+    }
+
+    async fn handle_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(input) = self.input.as_mut() else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Esc => self.input = None,
+            KeyCode::Enter => {
+                let username = input.trim().to_string();
+                self.input = None;
+                if !username.is_empty() {
+                    let previous = std::mem::replace(&mut self.username, username);
+                    if !self.load().await? {
+                        self.username = previous;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Moves by weeks along `columns` and by days along `rows` of the grid
+    /// as drawn, staying within the range.
+    fn move_cursor(&mut self, columns: i64, rows: i64) {
+        let (weeks, days) = match self.config.orientation {
+            Some(Orientation::Vertical) => (rows, columns),
+            _ => (columns, rows),
+        };
+        let Some((first, last)) = self.data.as_ref().and_then(ContributionData::date_range) else {
+            return;
+        };
+        let target = self.cursor + Duration::days(weeks * 7 + days);
+        self.cursor = target.clamp(first, last);
+    }
+
+    fn draw(&self) -> Result<()> {
+        let mut out = io::stdout();
+        queue!(out, Clear(ClearType::All))?;
+        let (width, height) = terminal::size()?;
+
+        let mut graph_height = 0;
+        let mut panel = Vec::new();
+        if let Some(data) = &self.data {
+            let year_specified = self.year.is_some();
+            let graph = generate_highlighted_graph(
+                &data.daily_contributions,
+                year_specified,
+                |date| date == self.cursor,
+                self.config,
+                color::enabled(),
+            );
+            for (row, line) in graph.lines().enumerate() {
+                queue!(out, MoveTo(0, row as u16), Print(line))?;
+            }
+            graph_height = graph.lines().count();
+
+            let count = data
+                .daily_contributions
+                .iter()
+                .find(|&&(date, _)| date == self.cursor)
+                .map_or(0, |&(_, count)| count);
+            let profile = self
+                .profile
+                .as_ref()
+                .filter(|(user, _)| *user == self.username)
+                .and_then(|(_, profile)| profile.as_ref());
+            let mut info = contribution_info(
+                &self.username,
+                &self.year_label(),
+                data,
+                profile,
+                self.config,
+            );
+            info.push(InfoLine::field(
+                "Selected",
+                format!("{} {}", self.cursor.weekday(), self.cursor),
+            ));
+            info.push(InfoLine::field("Contributions", count));
+            panel = colorize_info(&info, self.config, color::enabled());
+        }
+
+        // Day details go below the graph as they may be wide; the stats go
+        // beside it when there's room, otherwise below it
+        let (panel_column, panel_row) = if let Some(detail) = &self.detail {
+            panel = detail.lines().map(str::to_string).collect();
+            (0, graph_height + 1)
+        } else if (width as usize) >= self.graph_width + 3 + PANEL_WIDTH {
+            (self.graph_width + 3, 1)
+        } else {
+            (0, graph_height + 1)
+        };
+        for (index, line) in panel.iter().enumerate() {
+            queue!(
+                out,
+                MoveTo(panel_column as u16, (panel_row + index) as u16),
+                Print(line)
+            )?;
+        }
+
+        let footer = match &self.input {
+            Some(input) => format!("User: {}_", input),
+            None => self.status.clone(),
+        };
+        queue!(out, MoveTo(0, height.saturating_sub(1)), Print(footer))?;
+        out.flush()?;
+        Ok(())
+    }
+}
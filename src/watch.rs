@@ -0,0 +1,164 @@
+use crate::commands::Options;
+use crate::config_manager::Config;
+use crate::contribution_analyzer::ContributionData;
+use crate::contribution_source::ContributionSource;
+use crate::errors::FetchError;
+use crate::graph_generator::{colorize_graph_line, colorize_info, generate_highlighted_graph};
+use crate::info::InfoLine;
+use crate::timezone;
+use chrono::{NaiveDate, Utc};
+use crossterm::cursor::MoveTo;
+use crossterm::execute;
+use crossterm::terminal::{Clear, ClearType};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, IsTerminal};
+use std::time::Duration;
+
+/// Refreshing more often than this gains nothing and eats into rate limits,
+/// especially the unauthenticated ones of the public profile page.
+pub const MIN_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Parses intervals like "90", "90s", "5m" or "1h".
+pub fn parse_interval(interval: &str) -> Option<Duration> {
+    let interval = interval.trim();
+    let (number, unit) = match interval.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => interval.split_at(index),
+        None => (interval, "s"),
+    };
+    let number: u64 = number.parse().ok()?;
+    let seconds = match unit {
+        "s" => number,
+        "m" => number.checked_mul(60)?,
+        "h" => number.checked_mul(3600)?,
+        _ => return None,
+    };
+    Some(Duration::from_secs(seconds))
+}
+
+/// Re-fetches every `interval` and redraws in place until interrupted,
+/// highlighting the days whose count went up since the previous refresh.
+/// When rate limited, waits for the limit to reset before trying again.
+/// Only the first fetch failing is an error; later failures keep the last
+/// data on screen. Earlier years followed for streaks are fetched only once.
+/// The `warnings` stay below the status line.
+pub async fn run(
+    source: &ContributionSource,
+    username: &str,
+    interval: Duration,
+    options: &Options<'_>,
+    warnings: &[String],
+    info: impl Fn(&ContributionData) -> Vec<InfoLine>,
+) -> Result<(), FetchError> {
+    let Options {
+        config,
+        year,
+        year_specified,
+        colors,
+    } = *options;
+    let mut data: Option<ContributionData> = None;
+    let mut new_days: HashSet<NaiveDate> = HashSet::new();
+
+    loop {
+        let mut wait = interval;
+        let fetched = tokio::select! {
+            fetched = source.fetch(username, year, year_specified) => fetched,
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        };
+        let mut status = match fetched {
+            Ok(fresh) => {
+                let added = match &data {
+                    Some(previous) => {
+                        let before: HashMap<NaiveDate, u32> =
+                            previous.daily_contributions.iter().copied().collect();
+                        new_days.clear();
+                        let mut added = 0;
+                        for &(date, count) in &fresh.daily_contributions {
+                            let old = before.get(&date).copied().unwrap_or(0);
+                            if count > old {
+                                new_days.insert(date);
+                                added += count - old;
+                            }
+                        }
+                        added
+                    }
+                    None => 0,
+                };
+                data = Some(fresh);
+                format!(
+                    "Updated {}, {} new since the last refresh",
+                    timezone::zone().localize(&Utc::now()).format("%H:%M:%S"),
+                    added
+                )
+            }
+            Err(e) if data.is_none() => return Err(e),
+            Err(FetchError::RateLimited { reset_at }) => {
+                if let Some(reset_at) = reset_at {
+                    if let Ok(until_reset) = (reset_at - Utc::now()).to_std() {
+                        wait = wait.max(until_reset);
+                    }
+                }
+                "Rate limited, waiting for the limit to reset".to_string()
+            }
+            Err(e) => format!("Refresh failed: {}", e),
+        };
+
+        for warning in warnings {
+            status += &format!("\nWarning: {}", warning);
+        }
+        if let Some(data) = &data {
+            draw(
+                data,
+                &info(data),
+                &new_days,
+                year_specified,
+                &status,
+                config,
+                colors,
+            );
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+fn draw(
+    data: &ContributionData,
+    info: &[InfoLine],
+    new_days: &HashSet<NaiveDate>,
+    year_specified: bool,
+    status: &str,
+    config: &Config,
+    colors: bool,
+) {
+    // Redraw in place on a terminal; logs and pipes get one refresh after
+    // another instead of escape sequences
+    if io::stdout().is_terminal() {
+        let _ = execute!(io::stdout(), MoveTo(0, 0), Clear(ClearType::All));
+    } else {
+        println!("{}", "-".repeat(40));
+    }
+    print!(
+        "{}",
+        generate_highlighted_graph(
+            &data.daily_contributions,
+            year_specified,
+            |date| new_days.contains(&date),
+            config,
+            colors,
+        )
+    );
+    println!();
+    println!(
+        "{}",
+        colorize_graph_line("Less   ░░  ▒▒  ▓▓  ██ More", config, colors)
+    );
+    println!();
+    for line in colorize_info(info, config, colors) {
+        println!("{}", line);
+    }
+    println!();
+    println!("{}", status);
+}